@@ -0,0 +1,92 @@
+use std::fmt;
+
+/// The result of a single puzzle part.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+    /// The puzzle has no answer for this part (e.g. the last day only has one part)
+    Unsolved,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Unsigned(answer) => answer.fmt(f),
+            Answer::Signed(answer) => answer.fmt(f),
+            Answer::Float(answer) => answer.fmt(f),
+            Answer::Text(answer) => answer.fmt(f),
+            Answer::Unsolved => f.write_str("-"),
+        }
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(answer: u64) -> Self {
+        Answer::Unsigned(answer)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(answer: usize) -> Self {
+        Answer::Unsigned(answer as u64)
+    }
+}
+
+// too large for `Unsigned`, but still printed the same way
+impl From<u128> for Answer {
+    fn from(answer: u128) -> Self {
+        match u64::try_from(answer) {
+            Ok(answer) => Answer::Unsigned(answer),
+            Err(_) => Answer::Text(answer.to_string()),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(answer: i64) -> Self {
+        Answer::Signed(answer)
+    }
+}
+
+impl From<f32> for Answer {
+    fn from(answer: f32) -> Self {
+        Answer::Float(answer.into())
+    }
+}
+
+impl From<f64> for Answer {
+    fn from(answer: f64) -> Self {
+        Answer::Float(answer)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(answer: String) -> Self {
+        Answer::Text(answer)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(answer: &str) -> Self {
+        Answer::Text(answer.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::from(1150u64).to_string(), "1150");
+        assert_eq!(Answer::from(-3i64).to_string(), "-3");
+        assert_eq!(Answer::from(7u128), Answer::Unsigned(7));
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Answer::from(42047840f32).to_string(), "42047840");
+        assert_eq!(Answer::from("abc").to_string(), "abc");
+        assert_eq!(Answer::Unsolved.to_string(), "-");
+    }
+}
@@ -0,0 +1,914 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use ndarray::{Array2, array};
+
+use crate::{Grid, grid::Position};
+
+/// The weights of the cells around a cell that make up its sum, centred on the middle entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kernel {
+    weights: Array2<i32>,
+}
+
+impl Kernel {
+    /// `None` unless both sides are odd, otherwise there is no centre.
+    pub fn new(weights: Array2<i32>) -> Option<Self> {
+        let (rows, columns) = weights.dim();
+        (rows % 2 == 1 && columns % 2 == 1).then_some(Self { weights })
+    }
+
+    /// The 8 cells around a cell, diagonals included.
+    pub fn moore() -> Self {
+        Self {
+            weights: array![[1, 1, 1], [1, 0, 1], [1, 1, 1]],
+        }
+    }
+
+    /// The 4 orthogonally connected cells around a cell.
+    pub fn von_neumann() -> Self {
+        Self {
+            weights: array![[0, 1, 0], [1, 0, 1], [0, 1, 0]],
+        }
+    }
+
+    pub fn weights(&self) -> &Array2<i32> {
+        &self.weights
+    }
+
+    fn is_positive(&self) -> bool {
+        self.weights.iter().all(|weight| *weight >= 0)
+    }
+
+    fn radius(&self) -> (usize, usize) {
+        let (rows, columns) = self.weights.dim();
+        (rows / 2, columns / 2)
+    }
+
+    /// Every non-zero weight with its offset from the centre.
+    fn entries(&self) -> impl Iterator<Item = ((isize, isize), i32)> + '_ {
+        let (rows, columns) = self.radius();
+        self.weights
+            .indexed_iter()
+            .filter(|(_, weight)| **weight != 0)
+            .map(move |((row, column), weight)| {
+                let offset = (
+                    row as isize - rows as isize,
+                    column as isize - columns as isize,
+                );
+                (offset, *weight)
+            })
+    }
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Self::moore()
+    }
+}
+
+/// What the next state of a cell is, given its current state and the sum of its kernel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Dead cells with a sum in `birth` come alive, live cells with a sum in `survival` stay
+    /// alive and every other cell is dead, e.g. `B3/S23` for Conway's Game of Life.
+    Life { birth: Vec<i32>, survival: Vec<i32> },
+    /// Live cells with a sum below `min` are removed and nothing is ever born.
+    Removal { min: i32 },
+}
+
+impl Rule {
+    pub fn conway() -> Self {
+        Rule::Life {
+            birth: vec![3],
+            survival: vec![2, 3],
+        }
+    }
+
+    pub fn next(&self, alive: bool, sum: i32) -> bool {
+        match self {
+            Rule::Life { birth, .. } if !alive => birth.contains(&sum),
+            Rule::Life { survival, .. } => survival.contains(&sum),
+            Rule::Removal { min } => alive && sum >= *min,
+        }
+    }
+
+    fn changes(&self, cell: i32) -> bool {
+        let alive = cell & ALIVE != 0;
+        self.next(alive, cell >> SUM_SHIFT) != alive
+    }
+}
+
+/// Parses the `B<digits>/S<digits>` notation of Life-like rules.
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        fn sums(sums: &str) -> impl Iterator<Item = Option<i32>> + '_ {
+            sums.chars()
+                .map(|sum| sum.to_digit(10).map(|sum| sum as i32))
+        }
+
+        let parts = rule.split_once('/').and_then(|(birth, survival)| {
+            let birth = sums(birth.strip_prefix('B')?).collect::<Option<_>>()?;
+            let survival = sums(survival.strip_prefix('S')?).collect::<Option<_>>()?;
+            Some((birth, survival))
+        });
+        match parts {
+            Some((birth, survival)) => Ok(Rule::Life { birth, survival }),
+            None => Err(format!("unsupported rule `{rule}`, expected e.g. `B3/S23`")),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Life { birth, survival } => {
+                f.write_str("B")?;
+                birth.iter().try_for_each(|sum| write!(f, "{sum}"))?;
+                f.write_str("/S")?;
+                survival.iter().try_for_each(|sum| write!(f, "{sum}"))
+            }
+            Rule::Removal { min } => write!(f, "remove below {min}"),
+        }
+    }
+}
+
+/// How the kernel sees the cells beyond the edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// Outside of the grid every cell is dead
+    #[default]
+    Zeros,
+    /// The grid wraps around at the edges (a torus)
+    Wrap,
+    /// The grid is mirrored at the edges, without repeating the edge itself
+    Reflect,
+}
+
+impl Boundary {
+    /// Where `idx` ends up along a side of length `len`, if it is a cell at all.
+    fn index(self, idx: isize, len: usize) -> Option<usize> {
+        let len = len as isize;
+        match self {
+            Boundary::Zeros => (0..len).contains(&idx).then_some(idx as usize),
+            Boundary::Wrap => Some(idx.rem_euclid(len) as usize),
+            Boundary::Reflect if len == 1 => Some(0),
+            Boundary::Reflect => {
+                let period = 2 * (len - 1);
+                let idx = idx.rem_euclid(period);
+                Some(if idx < len { idx } else { period - idx } as usize)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Update {
+    /// Every cell of a generation is decided on the grid as it was before that generation
+    #[default]
+    Synchronous,
+    /// Cells are decided one by one in row-major order, each seeing the changes before it
+    Sequential,
+}
+
+/// Which cells are stored, and where.
+enum Layout {
+    /// Every cell in row-major order
+    Dense,
+    /// Only the cells alive at the start in row-major order, for a removal rule on a huge grid
+    /// that is mostly dead, since nothing is ever born
+    Sparse {
+        positions: Vec<Position>,
+        indices: HashMap<Position, usize>,
+    },
+}
+
+/// A 2D cellular automaton of live and dead cells, stepped one generation at a time.
+///
+/// Every cell keeps the sum of its kernel, updated as the cells around it change, so a
+/// generation only costs as much as the cells changing in it. Iterating yields the positions
+/// that changed in every generation until one changes nothing.
+///
+/// A synchronous [`Rule::Removal`] with a kernel without negative weights takes a faster path:
+/// sums only ever drop, so a cell is removed exactly when its sum drops below the minimum and
+/// nothing else has to be checked.
+pub struct Automaton {
+    rows: usize,
+    columns: usize,
+    layout: Layout,
+    // the state of every stored cell and the sum of its kernel in a single lookup, see `ALIVE`
+    cells: Vec<i32>,
+    kernel: Kernel,
+    rule: Rule,
+    boundary: Boundary,
+    update: Update,
+    generation: usize,
+    // the kernel and boundary can still change until the first step counts the sums
+    counted: bool,
+    // flat offsets of the kernel, for cells far enough from the edge to ignore the boundary
+    offsets: Vec<(isize, i32)>,
+    // the cells changing in the next synchronous generation, `None` to check every cell
+    pending: Option<Vec<usize>>,
+}
+
+// the low bits of a cell are flags, the sum of its kernel is stored above them
+const ALIVE: i32 = 1;
+const QUEUED: i32 = 2;
+// every cell with this cell in its kernel sees it once, without crossing the boundary
+const INTERIOR: i32 = 4;
+const SUM_SHIFT: u32 = 3;
+
+impl Automaton {
+    pub fn new(cells: Grid<bool>, rule: Rule) -> Self {
+        let (rows, columns) = (cells.rows(), cells.columns());
+        // a grid in row-major order converts as one slice, far quicker than cell by cell
+        let cells = match cells.as_array().as_slice() {
+            Some(cells) => cells.iter().map(|alive| i32::from(*alive)).collect(),
+            None => cells.iter().map(|alive| i32::from(*alive)).collect(),
+        };
+
+        Self {
+            rows,
+            columns,
+            layout: Layout::Dense,
+            cells,
+            kernel: Kernel::default(),
+            rule,
+            boundary: Boundary::default(),
+            update: Update::default(),
+            generation: 0,
+            counted: false,
+            offsets: Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Only stores the `alive` cells of a `rows` × `columns` grid, `None` unless `rule` is a
+    /// [`Rule::Removal`], the only rule that never needs a dead cell.
+    pub fn sparse(rows: usize, columns: usize, alive: Vec<Position>, rule: Rule) -> Option<Self> {
+        if !matches!(rule, Rule::Removal { .. }) {
+            return None;
+        }
+
+        let mut positions: Vec<_> = alive
+            .into_iter()
+            .filter(|(row, column)| *row < rows && *column < columns)
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let indices = positions
+            .iter()
+            .enumerate()
+            .map(|(idx, position)| (*position, idx))
+            .collect();
+        let mut automaton = Self::new(Grid::new(Array2::from_elem((0, 0), false)), rule);
+        automaton.rows = rows;
+        automaton.columns = columns;
+        automaton.cells = vec![ALIVE; positions.len()];
+        automaton.layout = Layout::Sparse { positions, indices };
+        Some(automaton)
+    }
+
+    pub fn with_kernel(mut self, kernel: Kernel) -> Self {
+        self.kernel = kernel;
+        self.uncount();
+        self
+    }
+
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self.uncount();
+        self
+    }
+
+    pub fn with_update(mut self, update: Update) -> Self {
+        self.update = update;
+        self.pending = None;
+        self
+    }
+
+    /// The number of generations that changed any cell so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn is_alive(&self, position: Position) -> bool {
+        self.index(position).is_some_and(|idx| self.alive(idx))
+    }
+
+    /// Every cell of the grid, also for a sparse automaton.
+    pub fn cells(&self) -> Grid<bool> {
+        match &self.layout {
+            Layout::Dense => {
+                let cells = self.cells.iter().map(|cell| cell & ALIVE != 0).collect();
+                Grid::new(Array2::from_shape_vec((self.rows, self.columns), cells).unwrap())
+            }
+            Layout::Sparse { positions, .. } => {
+                let mut cells = Array2::from_elem((self.rows, self.columns), false);
+                for (idx, position) in positions.iter().enumerate() {
+                    cells[*position] = self.alive(idx);
+                }
+                Grid::new(cells)
+            }
+        }
+    }
+
+    /// Advances a single generation and returns the positions that changed, in no particular
+    /// order. Nothing changing means the automaton is stable.
+    pub fn step(&mut self) -> Vec<Position> {
+        if !self.counted {
+            self.count_sums();
+        }
+
+        let changed = match (&self.rule, self.update) {
+            (Rule::Removal { min }, Update::Synchronous) if self.kernel.is_positive() => {
+                self.step_removal(*min)
+            }
+            (_, Update::Synchronous) => self.step_synchronous(),
+            (_, Update::Sequential) => self.step_sequential(),
+        };
+        if !changed.is_empty() {
+            self.generation += 1;
+        }
+
+        changed
+            .into_iter()
+            .map(|idx| self.cell_position(idx))
+            .collect()
+    }
+
+    fn step_removal(&mut self, min: i32) -> Vec<usize> {
+        let removed: Vec<usize> = match self.pending.take() {
+            Some(pending) => pending,
+            None => self.changing(),
+        };
+
+        // the cells removed this generation are either dead already or below the minimum, so
+        // only the cells still alive that drop below it are removed next
+        let mut pending = Vec::new();
+        let mut lower = |cells: &mut [i32], idx: usize, weight: i32| {
+            let before = cells[idx];
+            cells[idx] -= weight << SUM_SHIFT;
+            if before & ALIVE != 0 && before >> SUM_SHIFT >= min && cells[idx] >> SUM_SHIFT < min {
+                pending.push(idx);
+            }
+        };
+        for idx in &removed {
+            self.cells[*idx] ^= ALIVE;
+            if self.is_interior(*idx) {
+                for (offset, weight) in &self.offsets {
+                    lower(&mut self.cells, idx.wrapping_add_signed(-offset), *weight);
+                }
+            } else {
+                for (around, weight) in self.edge_affected(*idx) {
+                    lower(&mut self.cells, around, weight);
+                }
+            }
+        }
+        self.pending = Some(pending);
+
+        removed
+    }
+
+    fn step_synchronous(&mut self) -> Vec<usize> {
+        let changed: Vec<usize> = match self.pending.take() {
+            Some(pending) => pending,
+            None => self.changing(),
+        };
+        for idx in &changed {
+            self.flip(*idx);
+        }
+
+        // a cell can only change next if it or a cell in its kernel just changed
+        let mut pending = Vec::new();
+        let mut check = |automaton: &mut Self, idx: usize| {
+            if automaton.cells[idx] & QUEUED == 0 && automaton.changes(idx) {
+                automaton.cells[idx] |= QUEUED;
+                pending.push(idx);
+            }
+        };
+        for idx in &changed {
+            check(self, *idx);
+            if self.is_interior(*idx) {
+                for offset in 0..self.offsets.len() {
+                    check(self, idx.wrapping_add_signed(-self.offsets[offset].0));
+                }
+            } else {
+                for (around, _) in self.edge_affected(*idx) {
+                    check(self, around);
+                }
+            }
+        }
+        for idx in &pending {
+            self.cells[*idx] &= !QUEUED;
+        }
+        self.pending = Some(pending);
+
+        changed
+    }
+
+    fn step_sequential(&mut self) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for idx in 0..self.cells.len() {
+            if self.changes(idx) {
+                self.flip(idx);
+                changed.push(idx);
+            }
+        }
+        changed
+    }
+
+    /// Every cell that changes in the next generation.
+    fn changing(&self) -> Vec<usize> {
+        match self.rule {
+            // checked without looking the rule up for every cell
+            Rule::Removal { min } => {
+                self.collect(|cell| (cell & ALIVE != 0) & (cell >> SUM_SHIFT < min))
+            }
+            Rule::Life { .. } => self.collect(|cell| self.rule.changes(cell)),
+        }
+    }
+
+    fn collect(&self, changes: impl Fn(i32) -> bool) -> Vec<usize> {
+        const CHUNK: usize = 1024;
+
+        // collected without branching on every cell, whether one changes is rarely predictable
+        let mut changing = Vec::new();
+        let mut found = [0; CHUNK];
+        for (chunk, cells) in self.cells.chunks(CHUNK).enumerate() {
+            let mut len = 0;
+            for (idx, cell) in cells.iter().enumerate() {
+                found[len] = chunk * CHUNK + idx;
+                len += usize::from(changes(*cell));
+            }
+            changing.extend_from_slice(&found[..len]);
+        }
+        changing
+    }
+
+    fn cell_position(&self, idx: usize) -> Position {
+        match &self.layout {
+            Layout::Dense => (idx / self.columns, idx % self.columns),
+            Layout::Sparse { positions, .. } => positions[idx],
+        }
+    }
+
+    /// Where the cell at `position` is stored, if it is.
+    fn index(&self, (row, column): Position) -> Option<usize> {
+        match &self.layout {
+            Layout::Dense => {
+                (row < self.rows && column < self.columns).then_some(row * self.columns + column)
+            }
+            Layout::Sparse { indices, .. } => indices.get(&(row, column)).copied(),
+        }
+    }
+
+    fn alive(&self, idx: usize) -> bool {
+        self.cells[idx] & ALIVE != 0
+    }
+
+    fn changes(&self, idx: usize) -> bool {
+        self.rule.changes(self.cells[idx])
+    }
+
+    fn uncount(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell &= ALIVE);
+        self.counted = false;
+        self.pending = None;
+    }
+
+    fn count_sums(&mut self) {
+        if let Layout::Sparse { .. } = self.layout {
+            for idx in 0..self.cells.len() {
+                self.cells[idx] += self.kernel_sum(self.cell_position(idx)) << SUM_SHIFT;
+            }
+            self.counted = true;
+            return;
+        }
+
+        self.offsets = self
+            .kernel
+            .entries()
+            .map(|((row, column), weight)| (row * self.columns as isize + column, weight))
+            .collect();
+
+        let (rows, columns) = self.kernel.radius();
+        let interior = columns..self.columns.saturating_sub(columns);
+        for row in 0..self.rows {
+            let start = row * self.columns;
+            if !(rows..self.rows.saturating_sub(rows)).contains(&row) || interior.is_empty() {
+                for column in 0..self.columns {
+                    self.cells[start + column] += self.kernel_sum((row, column)) << SUM_SHIFT;
+                }
+                continue;
+            }
+
+            for column in (0..interior.start).chain(interior.end..self.columns) {
+                self.cells[start + column] += self.kernel_sum((row, column)) << SUM_SHIFT;
+            }
+            // summed apart from the cells, the kernel reads the flags of cells counted before
+            let mut sums = vec![0; interior.len()];
+            for (offset, weight) in &self.offsets {
+                let cells = &self.cells[(start + interior.start).wrapping_add_signed(*offset)..];
+                for (sum, cell) in sums.iter_mut().zip(cells) {
+                    *sum += weight * (cell & ALIVE);
+                }
+            }
+            for (cell, sum) in self.cells[start + interior.start..].iter_mut().zip(sums) {
+                *cell += sum << SUM_SHIFT;
+            }
+        }
+
+        // a mirrored edge sees the cells up to the radius inside it a second time
+        let (rows, columns) = match self.boundary {
+            Boundary::Reflect => (rows + 1, columns + 1),
+            Boundary::Zeros | Boundary::Wrap => (rows, columns),
+        };
+        let interior = columns..self.columns.saturating_sub(columns);
+        if !interior.is_empty() {
+            for row in rows..self.rows.saturating_sub(rows) {
+                let start = row * self.columns;
+                for cell in &mut self.cells[start + interior.start..start + interior.end] {
+                    *cell |= INTERIOR;
+                }
+            }
+        }
+        self.counted = true;
+    }
+
+    /// The sum of the kernel around `position`, counted from scratch.
+    fn kernel_sum(&self, position: Position) -> i32 {
+        self.kernel
+            .entries()
+            .filter_map(|(offset, weight)| {
+                let around = self.index(self.neighbour(position, offset)?)?;
+                Some(weight * i32::from(self.alive(around)))
+            })
+            .sum()
+    }
+
+    fn flip(&mut self, idx: usize) {
+        self.cells[idx] ^= ALIVE;
+        self.add(idx, if self.alive(idx) { 1 } else { -1 });
+    }
+
+    /// Adds `delta` times its weight to the sum of every cell with `idx` in its kernel.
+    fn add(&mut self, idx: usize, delta: i32) {
+        let delta = delta << SUM_SHIFT;
+        if self.is_interior(idx) {
+            for (offset, weight) in &self.offsets {
+                self.cells[idx.wrapping_add_signed(-offset)] += weight * delta;
+            }
+        } else {
+            for (around, weight) in self.edge_affected(idx) {
+                self.cells[around] += weight * delta;
+            }
+        }
+    }
+
+    /// Whether every cell with `idx` in its kernel sees it once, without crossing the boundary.
+    fn is_interior(&self, idx: usize) -> bool {
+        self.cells[idx] & INTERIOR != 0
+    }
+
+    /// The cells with `idx` in their kernel and its weight there, for cells near the edge and
+    /// every cell of a sparse automaton.
+    fn edge_affected(&self, idx: usize) -> Vec<(usize, i32)> {
+        let (row, column) = self.cell_position(idx);
+        let (rows, columns) = self.kernel.radius();
+        let (rows, columns) = (rows as isize, columns as isize);
+
+        // a boundary never moves a cell further away than the kernel reaches, apart from wrapping
+        let side = |idx: usize, offset: isize, len: usize| match self.boundary {
+            Boundary::Wrap => Boundary::Wrap.index(idx as isize + offset, len),
+            _ => Boundary::Zeros.index(idx as isize + offset, len),
+        };
+        let mut around: Vec<Position> = (-rows..=rows)
+            .filter_map(|offset| side(row, offset, self.rows))
+            .flat_map(|row| {
+                (-columns..=columns)
+                    .filter_map(move |offset| side(column, offset, self.columns))
+                    .map(move |column| (row, column))
+            })
+            .collect();
+        around.sort_unstable();
+        around.dedup();
+
+        around
+            .into_iter()
+            .filter_map(|position| Some((position, self.index(position)?)))
+            .flat_map(|(position, around)| {
+                self.kernel
+                    .entries()
+                    .filter(move |(offset, _)| {
+                        self.neighbour(position, *offset) == Some((row, column))
+                    })
+                    .map(move |(_, weight)| (around, weight))
+            })
+            .collect()
+    }
+
+    fn neighbour(
+        &self,
+        (row, column): Position,
+        (rows, columns): (isize, isize),
+    ) -> Option<Position> {
+        let row = self.boundary.index(row as isize + rows, self.rows)?;
+        let column = self
+            .boundary
+            .index(column as isize + columns, self.columns)?;
+        Some((row, column))
+    }
+}
+
+impl Iterator for Automaton {
+    type Item = Vec<Position>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let changed = self.step();
+        (!changed.is_empty()).then_some(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &str) -> Grid<bool> {
+        let rows = rows
+            .lines()
+            .map(|row| row.chars().map(|cell| cell == '#').collect())
+            .collect();
+        Grid::from_rows(rows).unwrap()
+    }
+
+    fn render(automaton: &Automaton) -> String {
+        automaton
+            .cells()
+            .render(|alive| if *alive { '#' } else { '.' })
+    }
+
+    // every sum counted from scratch, as the reference for the kept sums
+    fn counted_sums(automaton: &Automaton) -> Vec<i32> {
+        (0..automaton.cells.len())
+            .map(|idx| automaton.kernel_sum((idx / automaton.columns, idx % automaton.columns)))
+            .collect()
+    }
+
+    fn sums(automaton: &Automaton) -> Vec<i32> {
+        automaton
+            .cells
+            .iter()
+            .map(|cell| cell >> SUM_SHIFT)
+            .collect()
+    }
+
+    #[test]
+    fn test_blinker() {
+        let mut automaton =
+            Automaton::new(grid(".....\n..#..\n..#..\n..#..\n....."), Rule::conway());
+
+        let changed = automaton.step();
+        assert_eq!(changed.len(), 4);
+        assert_eq!(render(&automaton), ".....\n.....\n.###.\n.....\n.....");
+
+        automaton.step();
+        assert_eq!(render(&automaton), ".....\n..#..\n..#..\n..#..\n.....");
+        assert_eq!(automaton.generation(), 2);
+        assert_eq!(sums(&automaton), counted_sums(&automaton));
+    }
+
+    #[test]
+    fn test_glider_wraps() {
+        let start = grid(".#....\n..#...\n###...\n......\n......\n......");
+        let mut automaton =
+            Automaton::new(start.clone(), Rule::conway()).with_boundary(Boundary::Wrap);
+
+        // a glider moves one cell diagonally every 4 generations, back after going around
+        for _ in 0..4 * 6 {
+            automaton.step();
+            assert_eq!(sums(&automaton), counted_sums(&automaton));
+        }
+        assert_eq!(automaton.cells(), start);
+    }
+
+    #[test]
+    fn test_boundary() {
+        assert_eq!(Boundary::Zeros.index(-1, 4), None);
+        assert_eq!(Boundary::Wrap.index(-1, 4), Some(3));
+        assert_eq!(Boundary::Wrap.index(4, 4), Some(0));
+        assert_eq!(Boundary::Reflect.index(-1, 4), Some(1));
+        assert_eq!(Boundary::Reflect.index(4, 4), Some(2));
+        assert_eq!(Boundary::Reflect.index(-2, 1), Some(0));
+    }
+
+    #[test]
+    fn test_reflect() {
+        // the rows mirror each other, so every cell sees the top row twice or not at all
+        let mut automaton =
+            Automaton::new(grid("###\n..."), Rule::conway()).with_boundary(Boundary::Reflect);
+        automaton.step();
+
+        assert_eq!(sums(&automaton), [2, 2, 2, 6, 6, 6]);
+        assert_eq!(sums(&automaton), counted_sums(&automaton));
+        assert_eq!(render(&automaton), "###\n...");
+    }
+
+    // every generation decided from scratch, as the reference for the automaton
+    fn brute_force_step(
+        cells: &Grid<bool>,
+        rule: &Rule,
+        kernel: &Kernel,
+        boundary: Boundary,
+    ) -> Grid<bool> {
+        let mut next = cells.clone();
+        for ((row, column), alive) in cells.indexed_iter() {
+            let sum = kernel
+                .entries()
+                .filter_map(|((rows, columns), weight)| {
+                    let row = boundary.index(row as isize + rows, cells.rows())?;
+                    let column = boundary.index(column as isize + columns, cells.columns())?;
+                    Some(weight * i32::from(cells[(row, column)]))
+                })
+                .sum();
+            next[(row, column)] = rule.next(*alive, sum);
+        }
+        next
+    }
+
+    // a deterministic pseudo random grid, about a third alive
+    fn generated(rows: usize, columns: usize) -> Grid<bool> {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        Grid::new(Array2::from_shape_fn((rows, columns), |_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state.is_multiple_of(3)
+        }))
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let kernels = [
+            Kernel::moore(),
+            Kernel::von_neumann(),
+            Kernel::new(Array2::ones((5, 5))).unwrap(),
+        ];
+        for (rows, columns) in [(5, 5), (8, 9), (12, 7)] {
+            for kernel in &kernels {
+                for boundary in [Boundary::Zeros, Boundary::Wrap, Boundary::Reflect] {
+                    let mut expected = generated(rows, columns);
+                    let mut automaton = Automaton::new(expected.clone(), Rule::conway())
+                        .with_kernel(kernel.clone())
+                        .with_boundary(boundary);
+
+                    for generation in 0..8 {
+                        automaton.step();
+                        expected = brute_force_step(&expected, &Rule::conway(), kernel, boundary);
+                        let case = (rows, columns, kernel.radius(), boundary, generation);
+                        assert_eq!(automaton.cells(), expected, "{case:?}");
+                        assert_eq!(sums(&automaton), counted_sums(&automaton), "{case:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_removal() {
+        let automaton = Automaton::new(grid("###\n###\n###"), Rule::Removal { min: 4 });
+        let rounds: Vec<_> = automaton.map(|changed| changed.len()).collect();
+
+        // the corners first, then the edges and the centre last
+        assert_eq!(rounds, [4, 4, 1]);
+    }
+
+    #[test]
+    fn test_removal_matches_brute_force() {
+        let kernels = [
+            Kernel::moore(),
+            Kernel::new(Array2::ones((5, 5))).unwrap(),
+            // negative weights can raise a sum again, which the removal fast path can't follow
+            Kernel::new(array![[1, 1, 1], [1, 0, -1], [1, 1, 1]]).unwrap(),
+        ];
+        for (rows, columns) in [(5, 5), (8, 9), (12, 7)] {
+            for kernel in &kernels {
+                for boundary in [Boundary::Zeros, Boundary::Wrap, Boundary::Reflect] {
+                    let rule = Rule::Removal { min: 3 };
+                    let mut expected = generated(rows, columns).map(|dead| !dead);
+                    let mut automaton = Automaton::new(expected.clone(), rule.clone())
+                        .with_kernel(kernel.clone())
+                        .with_boundary(boundary);
+
+                    for generation in 0..6 {
+                        automaton.step();
+                        expected = brute_force_step(&expected, &rule, kernel, boundary);
+                        let case = (rows, columns, kernel.radius(), boundary, generation);
+                        assert_eq!(automaton.cells(), expected, "{case:?}");
+                        assert_eq!(sums(&automaton), counted_sums(&automaton), "{case:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sparse() {
+        let rule = Rule::Removal { min: 3 };
+        assert!(Automaton::sparse(3, 3, vec![(0, 0)], Rule::conway()).is_none());
+
+        let cells = generated(12, 7).map(|dead| !dead);
+        let alive: Vec<_> = cells
+            .indexed_iter()
+            .filter(|(_, alive)| **alive)
+            .map(|(position, _)| position)
+            .collect();
+        for boundary in [Boundary::Zeros, Boundary::Wrap, Boundary::Reflect] {
+            for update in [Update::Synchronous, Update::Sequential] {
+                let dense = Automaton::new(cells.clone(), rule.clone())
+                    .with_boundary(boundary)
+                    .with_update(update);
+                let mut sparse = Automaton::sparse(12, 7, alive.clone(), rule.clone())
+                    .unwrap()
+                    .with_boundary(boundary)
+                    .with_update(update);
+
+                for mut changed in dense {
+                    let mut sparse_changed = sparse.step();
+                    changed.sort_unstable();
+                    sparse_changed.sort_unstable();
+                    assert_eq!(sparse_changed, changed, "{boundary:?} {update:?}");
+                }
+                assert!(sparse.step().is_empty());
+            }
+        }
+
+        // only the live cells are stored, whatever the size of the grid
+        let side = 1 << 40;
+        let mut huge = Automaton::sparse(side, side, vec![(0, 0), (side - 1, 5)], rule).unwrap();
+        assert_eq!(huge.step().len(), 2);
+        assert!(!huge.is_alive((0, 0)));
+        assert_eq!(huge.generation(), 1);
+    }
+
+    #[test]
+    fn test_sequential() {
+        let paper = grid("####");
+
+        let synchronous = Automaton::new(paper.clone(), Rule::Removal { min: 2 });
+        let rounds: Vec<_> = synchronous.map(|changed| changed.len()).collect();
+        assert_eq!(rounds, [2, 2]);
+
+        // removing the first cell leaves the second with a single neighbour before it is checked
+        let mut sequential =
+            Automaton::new(paper, Rule::Removal { min: 2 }).with_update(Update::Sequential);
+        assert_eq!(sequential.step().len(), 4);
+        assert!(sequential.step().is_empty());
+    }
+
+    #[test]
+    fn test_kernel() {
+        assert!(Kernel::new(array![[1, 1]]).is_none());
+
+        // the cells two apart along the row
+        let kernel = Kernel::new(array![[1, 0, 0, 0, 1]]).unwrap();
+        let mut automaton = Automaton::new(grid("#.#.#"), Rule::Removal { min: 1 })
+            .with_kernel(kernel)
+            .with_boundary(Boundary::Wrap);
+        automaton.step();
+
+        assert_eq!(sums(&automaton), counted_sums(&automaton));
+        assert_eq!(render(&automaton), "#.#.#");
+    }
+
+    #[test]
+    fn test_kernel_larger_than_grid() {
+        let kernel = Kernel::new(Array2::ones((5, 5))).unwrap();
+        for boundary in [Boundary::Zeros, Boundary::Wrap, Boundary::Reflect] {
+            let mut automaton = Automaton::new(grid("#..\n.#."), "B1/S1".parse().unwrap())
+                .with_kernel(kernel.clone())
+                .with_boundary(boundary);
+
+            for _ in 0..3 {
+                automaton.step();
+                assert_eq!(sums(&automaton), counted_sums(&automaton), "{boundary:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_rule_notation() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::conway()));
+        assert_eq!(Rule::conway().to_string(), "B3/S23");
+        assert_eq!(
+            "B36/S".parse(),
+            Ok(Rule::Life {
+                birth: vec![3, 6],
+                survival: vec![]
+            })
+        );
+        assert!("3/23".parse::<Rule>().is_err());
+        assert!("B3/Sx".parse::<Rule>().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use winnow::error::{ContextError, StrContext};
+
+/// Puzzle input that could not be parsed, located by line and column (both 1-based).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    day: u8,
+    line: usize,
+    column: usize,
+    label: Option<String>,
+    expected: Vec<String>,
+}
+
+impl ParseError {
+    /// Builds an error for the parser failure `error` at byte `offset` of `input`.
+    pub fn new(day: u8, input: &str, offset: usize, error: &ContextError) -> Self {
+        let consumed = &input[..offset];
+        let line_start = consumed.rfind('\n').map_or(0, |idx| idx + 1);
+
+        let label = error.context().find_map(|context| match context {
+            StrContext::Label(label) => Some(label.to_string()),
+            _ => None,
+        });
+        let expected = error
+            .context()
+            .filter_map(|context| match context {
+                StrContext::Expected(expected) => Some(expected.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            day,
+            line: consumed.matches('\n').count() + 1,
+            column: consumed[line_start..].chars().count() + 1,
+            label,
+            expected,
+        }
+    }
+
+    /// Builds an error from the result of `Parser::parse`.
+    pub fn from_winnow(day: u8, error: winnow::error::ParseError<&str, ContextError>) -> Self {
+        Self::new(day, error.input(), error.offset(), error.inner())
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {} input, line {} column {}: ",
+            self.day, self.line, self.column
+        )?;
+
+        match &self.label {
+            Some(label) => write!(f, "invalid {label}")?,
+            None => f.write_str("unexpected input")?,
+        }
+
+        if let Some((last, rest)) = self.expected.split_last() {
+            f.write_str(", expected ")?;
+            for expected in rest {
+                write!(f, "{expected}, ")?;
+            }
+            if !rest.is_empty() {
+                f.write_str("or ")?;
+            }
+            f.write_str(last)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use winnow::{
+        Parser,
+        ascii::digit1,
+        combinator::{cut_err, separated},
+        error::{StrContext, StrContextValue},
+    };
+
+    use super::*;
+
+    fn parse_numbers(input: &mut &str) -> winnow::ModalResult<Vec<u64>> {
+        separated(
+            1..,
+            cut_err(
+                digit1
+                    .try_map(str::parse::<u64>)
+                    .context(StrContext::Label("number"))
+                    .context(StrContext::Expected(StrContextValue::Description("digit"))),
+            ),
+            "\n",
+        )
+        .parse_next(input)
+    }
+
+    #[test]
+    fn test_line_and_column() {
+        let input = "1\n22\n33x";
+        let error = parse_numbers
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(3, e))
+            .unwrap_err();
+
+        assert_eq!((error.day(), error.line(), error.column()), (3, 3, 3));
+        assert_eq!(
+            error.to_string(),
+            "day 3 input, line 3 column 3: unexpected input"
+        );
+    }
+
+    #[test]
+    fn test_expected_tokens() {
+        let input = "1\n\n";
+        let error = parse_numbers
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(3, e))
+            .unwrap_err();
+
+        assert_eq!((error.line(), error.column()), (2, 1));
+        assert_eq!(error.expected(), ["digit"]);
+        assert_eq!(
+            error.to_string(),
+            "day 3 input, line 2 column 1: invalid number, expected digit"
+        );
+    }
+}
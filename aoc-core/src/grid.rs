@@ -0,0 +1,462 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+use ndarray::{Array2, ArrayView1, Axis};
+use winnow::{
+    ModalResult, Parser,
+    ascii::{line_ending, multispace0},
+    combinator::{alt, cut_err, eof, opt, peek, preceded},
+    error::{ContextError, ErrMode, StrContext, StrContextValue},
+};
+
+/// A (row, column) position in a grid.
+pub type Position = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const ADJACENT: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular 2D grid indexed by (row, column), backed by an [`Array2`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(cells: Array2<T>) -> Self {
+        Self { cells }
+    }
+
+    /// Builds a grid from its rows, `None` if they differ in length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let columns = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != columns) {
+            return None;
+        }
+
+        let shape = (rows.len(), columns);
+        let cells = Array2::from_shape_vec(shape, rows.into_iter().flatten().collect()).ok()?;
+        Some(Self { cells })
+    }
+
+    /// Builds a grid from rows that may differ in length, the shorter ones padded with `fill`
+    /// at the end up to the longest.
+    pub fn from_ragged_rows(rows: Vec<Vec<T>>, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let shape = (rows.len(), columns);
+        let mut cells = Vec::with_capacity(shape.0 * columns);
+        for mut row in rows {
+            row.resize(columns, fill.clone());
+            cells.append(&mut row);
+        }
+        Self::new(Array2::from_shape_vec(shape, cells).unwrap())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.nrows()
+    }
+
+    pub fn columns(&self) -> usize {
+        self.cells.ncols()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn as_array(&self) -> &Array2<T> {
+        &self.cells
+    }
+
+    pub fn as_array_mut(&mut self) -> &mut Array2<T> {
+        &mut self.cells
+    }
+
+    pub fn into_array(self) -> Array2<T> {
+        self.cells
+    }
+
+    pub fn get(&self, (row, column): Position) -> Option<&T> {
+        self.cells.get((row, column))
+    }
+
+    pub fn get_mut(&mut self, (row, column): Position) -> Option<&mut T> {
+        self.cells.get_mut((row, column))
+    }
+
+    /// The position `(rows, columns)` away from `position`, if it is inside the grid.
+    pub fn offset(
+        &self,
+        (row, column): Position,
+        (rows, columns): (isize, isize),
+    ) -> Option<Position> {
+        let row = row.checked_add_signed(rows)?;
+        let column = column.checked_add_signed(columns)?;
+
+        (row < self.rows() && column < self.columns()).then_some((row, column))
+    }
+
+    /// The up to 4 orthogonally connected positions around `position`.
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        ORTHOGONAL
+            .iter()
+            .filter_map(move |offset| self.offset(position, *offset))
+    }
+
+    /// The up to 8 positions around `position`, diagonals included.
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        ADJACENT
+            .iter()
+            .filter_map(move |offset| self.offset(position, *offset))
+    }
+
+    /// All cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// All cells with their position in row-major order.
+    pub fn indexed_iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells.indexed_iter()
+    }
+
+    pub fn row_iter(&self) -> impl Iterator<Item = ArrayView1<'_, T>> {
+        self.cells.rows().into_iter()
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(self.cells.map(f))
+    }
+
+    /// Prints every cell as the character returned by `cell`, one line per row.
+    pub fn render(&self, mut cell: impl FnMut(&T) -> char) -> String {
+        self.row_iter()
+            .map(|row| row.iter().map(&mut cell).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn transposed(&self) -> Self {
+        Self::new(self.cells.t().as_standard_layout().into_owned())
+    }
+
+    /// Swaps the left and right side.
+    pub fn mirrored(&self) -> Self {
+        let mut cells = self.cells.clone();
+        cells.invert_axis(Axis(1));
+        Self::new(cells.as_standard_layout().into_owned())
+    }
+
+    /// Swaps the top and bottom side.
+    pub fn flipped(&self) -> Self {
+        let mut cells = self.cells.clone();
+        cells.invert_axis(Axis(0));
+        Self::new(cells.as_standard_layout().into_owned())
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn rotated(&self) -> Self {
+        self.transposed().mirrored()
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn rotated_back(&self) -> Self {
+        self.transposed().flipped()
+    }
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self::new(Array2::from_shape_vec((0, 0), Vec::new()).unwrap())
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: Position) -> &T {
+        &self.cells[position]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, position: Position) -> &mut T {
+        &mut self.cells[position]
+    }
+}
+
+impl<T> From<Array2<T>> for Grid<T> {
+    fn from(cells: Array2<T>) -> Self {
+        Self::new(cells)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, row) in self.row_iter().enumerate() {
+            if idx > 0 {
+                f.write_str("\n")?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a character map into a grid, one `cell` per character.
+///
+/// The first row sets the width, every following row must match it. A line that does not start
+/// with a cell ends the grid without being consumed, e.g. a blank line or the next section.
+/// An input without any cells is an empty grid.
+pub fn char_map<'a, T, P>(mut cell: P) -> impl Parser<&'a str, Grid<T>, ErrMode<ContextError>>
+where
+    P: Parser<&'a str, T, ErrMode<ContextError>>,
+{
+    move |input: &mut &'a str| {
+        let Some(first) = opt(cell.by_ref()).parse_next(input)? else {
+            return Ok(Grid::default());
+        };
+        let mut cells = vec![first];
+        while opt(row_end).parse_next(input)?.is_none() {
+            cells.push(cut_err(cell.by_ref()).parse_next(input)?);
+        }
+
+        let columns = cells.len();
+        let mut rows = 1;
+        loop {
+            let next_row = opt(preceded(line_ending, cell.by_ref())).parse_next(input)?;
+            let Some(first) = next_row else {
+                break;
+            };
+            cells.push(first);
+            for _ in 1..columns {
+                cells.push(cut_err(cell.by_ref()).parse_next(input)?);
+            }
+            cut_err(row_end).parse_next(input)?;
+            rows += 1;
+        }
+
+        // every row has already been checked to match the first
+        Ok(Grid::new(
+            Array2::from_shape_vec((rows, columns), cells).unwrap(),
+        ))
+    }
+}
+
+fn row_end(input: &mut &str) -> ModalResult<()> {
+    peek(alt((line_ending, eof)))
+        .void()
+        .context(StrContext::Label("row"))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "end of row",
+        )))
+        .parse_next(input)
+}
+
+/// Parses the rows of a character map that may differ in length, one `cell` per character, see
+/// [`Grid::from_ragged_rows`] to pad them.
+///
+/// Unlike [`char_map`] every line is a row, blank lines too, up to the end of the input. Only
+/// the whitespace after the line ending of the last row is left unconsumed.
+pub fn ragged_rows<'a, T, P>(
+    mut cell: P,
+) -> impl Parser<&'a str, Vec<Vec<T>>, ErrMode<ContextError>>
+where
+    P: Parser<&'a str, T, ErrMode<ContextError>>,
+{
+    move |input: &mut &'a str| {
+        // the rows end at the line ending after the last character that isn't whitespace
+        let last = input.trim_end().len();
+        let end = input[last..]
+            .find('\n')
+            .map_or(input.len(), |end| last + end);
+        let end = if end > last && input[..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+        let trailing = input.len() - end;
+
+        let mut rows = Vec::new();
+        while input.len() > trailing {
+            if !rows.is_empty() {
+                line_ending.parse_next(input)?;
+            }
+            // checked without a parser, a failed one builds an error for every cell
+            let mut row = Vec::new();
+            while !(input.is_empty() || input.starts_with('\n') || input.starts_with("\r\n")) {
+                row.push(cut_err(cell.by_ref()).parse_next(input)?);
+            }
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+}
+
+/// [`char_map`] followed by optional trailing whitespace.
+pub fn char_map_input<'a, T, P>(cell: P) -> impl Parser<&'a str, Grid<T>, ErrMode<ContextError>>
+where
+    P: Parser<&'a str, T, ErrMode<ContextError>>,
+{
+    (char_map(cell), multispace0).map(|(grid, _)| grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use winnow::combinator::alt;
+
+    use super::*;
+    use crate::ParseError;
+
+    fn cell(input: &mut &str) -> ModalResult<char> {
+        alt(('.', '#'))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('.')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('#')))
+            .parse_next(input)
+    }
+
+    fn grid(input: &str) -> Grid<char> {
+        char_map_input(cell).parse(input).unwrap()
+    }
+
+    #[test]
+    fn test_char_map() {
+        let grid = grid("#..\n.#.\n");
+
+        assert_eq!((grid.rows(), grid.columns()), (2, 3));
+        assert_eq!(grid[(1, 1)], '#');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.render(|c| *c), "#..\n.#.");
+        assert_eq!(grid.to_string(), "#..\n.#.");
+    }
+
+    #[test]
+    fn test_char_map_stops_at_other_lines() {
+        let (rest, grid) = char_map(cell).parse_peek("##\n#.\n\n0: 3").unwrap();
+
+        assert_eq!((grid.rows(), grid.columns()), (2, 2));
+        assert_eq!(rest, "\n\n0: 3");
+    }
+
+    #[test]
+    fn test_char_map_empty() {
+        assert!(grid("").is_empty());
+        assert!(grid("\n").is_empty());
+    }
+
+    #[test]
+    fn test_char_map_errors() {
+        let parse = |input| {
+            char_map_input(cell)
+                .parse(input)
+                .map_err(|e| ParseError::from_winnow(1, e))
+                .unwrap_err()
+        };
+
+        let error = parse("#..\n.x.");
+        assert_eq!((error.line(), error.column()), (2, 2));
+        assert_eq!(error.expected(), ["`.`", "`#`"]);
+
+        let error = parse("#x.\n...");
+        assert_eq!((error.line(), error.column()), (1, 2));
+        assert_eq!(error.expected(), ["`.`", "`#`"]);
+
+        let error = parse("#..\n.#..");
+        assert_eq!((error.line(), error.column()), (2, 4));
+        assert_eq!(error.expected(), ["end of row"]);
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let parse = |input| ragged_rows(cell).parse_peek(input).unwrap();
+
+        let (rest, rows) = parse("#..\n.\n\n..#\r\n \n");
+        assert_eq!(rest, "\r\n \n");
+        assert_eq!(rows.iter().map(Vec::len).collect::<Vec<_>>(), [3, 1, 0, 3]);
+
+        let grid = Grid::from_ragged_rows(rows, ' ');
+        assert_eq!(grid.render(|c| *c), "#..\n.  \n   \n..#");
+        assert_eq!(parse("\n \n"), ("\n \n", vec![]));
+        assert_eq!(parse("#"), ("", vec![vec!['#']]));
+    }
+
+    #[test]
+    fn test_ragged_rows_errors() {
+        let parse = |input| {
+            ragged_rows(cell)
+                .parse(input)
+                .map_err(|e| ParseError::from_winnow(1, e))
+                .unwrap_err()
+        };
+
+        let error = parse("#..\n.x");
+        assert_eq!((error.line(), error.column()), (2, 2));
+        assert_eq!(error.expected(), ["`.`", "`#`"]);
+
+        // whitespace inside a row is not trailing
+        let error = parse("#..\n. \n");
+        assert_eq!((error.line(), error.column()), (2, 2));
+        let error = parse("#\r");
+        assert_eq!((error.line(), error.column()), (1, 2));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = grid("...\n...\n...");
+
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbours8((0, 0)).count(), 3);
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbours8((2, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = grid("##.\n...");
+
+        assert_eq!(grid.transposed().render(|c| *c), "#.\n#.\n..");
+        assert_eq!(grid.rotated().render(|c| *c), ".#\n.#\n..");
+        assert_eq!(grid.rotated_back().render(|c| *c), "..\n#.\n#.");
+        assert_eq!(grid.mirrored().render(|c| *c), ".##\n...");
+        assert_eq!(grid.flipped().render(|c| *c), "...\n##.");
+        assert_eq!(grid.rotated().rotated().rotated().rotated(), grid);
+    }
+
+    #[test]
+    fn test_from_rows() {
+        assert_eq!(
+            Grid::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap()[(1, 0)],
+            3
+        );
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_none());
+        assert_eq!(
+            Grid::from_ragged_rows(vec![vec![1, 2], vec![3]], 0),
+            Grid::from_rows(vec![vec![1, 2], vec![3, 0]]).unwrap()
+        );
+    }
+}
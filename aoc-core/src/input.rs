@@ -0,0 +1,333 @@
+use std::{
+    env, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable overriding the input cache directory.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Name of the personal puzzle input, any other name is an extra input stored next to it.
+pub const DEFAULT_INPUT: &str = "input";
+
+/// Retrieves puzzle inputs that are not in the cache yet.
+pub trait Fetch {
+    fn fetch(&self, year: u16, day: u8) -> Result<String, InputError>;
+}
+
+/// Loads puzzle inputs from `<cache dir>/<year>/day<N>.txt` at runtime.
+///
+/// Missing inputs are requested from the fetch backend (if any) and written back to the cache.
+pub struct InputProvider {
+    cache_dir: PathBuf,
+    fetcher: Option<Box<dyn Fetch>>,
+}
+
+impl InputProvider {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_dir: cache_dir.into(),
+            fetcher: None,
+        }
+    }
+
+    /// Uses the directory in `AOC_INPUT_DIR`, falling back to `default_dir` when unset.
+    pub fn from_env(default_dir: impl Into<PathBuf>) -> Self {
+        match env::var_os(INPUT_DIR_VAR) {
+            Some(cache_dir) => Self::new(cache_dir),
+            None => Self::new(default_dir),
+        }
+    }
+
+    pub fn with_fetcher(mut self, fetcher: impl Fetch + 'static) -> Self {
+        self.fetcher = Some(Box::new(fetcher));
+        self
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    pub fn path(&self, year: u16, day: u8) -> PathBuf {
+        self.named_path(year, day, DEFAULT_INPUT)
+    }
+
+    /// Extra inputs live at `<cache dir>/<year>/day<N>-<name>.txt`.
+    pub fn named_path(&self, year: u16, day: u8, name: &str) -> PathBuf {
+        let file = match name {
+            DEFAULT_INPUT => format!("day{day}.txt"),
+            name => format!("day{day}-{name}.txt"),
+        };
+        self.cache_dir.join(year.to_string()).join(file)
+    }
+
+    /// Reads the cached input, fetching and caching it first if it is missing.
+    pub fn load(&self, year: u16, day: u8) -> Result<String, InputError> {
+        match (self.load_cached(year, day), &self.fetcher) {
+            (Err(InputError::Missing { .. }), Some(fetcher)) => {
+                let input = fetcher.fetch(year, day)?;
+                self.store(year, day, &input)?;
+                Ok(input)
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// Reads the named input, only the default input is ever fetched.
+    pub fn load_named(&self, year: u16, day: u8, name: &str) -> Result<String, InputError> {
+        match name {
+            DEFAULT_INPUT => self.load(year, day),
+            name => self.load_cached_named(year, day, name),
+        }
+    }
+
+    /// Reads the cached input without ever fetching it, an empty file counts as missing.
+    pub fn load_cached(&self, year: u16, day: u8) -> Result<String, InputError> {
+        self.load_cached_named(year, day, DEFAULT_INPUT)
+    }
+
+    pub fn load_cached_named(&self, year: u16, day: u8, name: &str) -> Result<String, InputError> {
+        let path = self.named_path(year, day, name);
+        match fs::read_to_string(&path) {
+            Ok(input) if !input.trim().is_empty() => Ok(input),
+            Ok(_) => Err(InputError::Missing { year, day, path }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Err(InputError::Missing { year, day, path })
+            }
+            Err(source) => Err(InputError::Io { path, source }),
+        }
+    }
+
+    fn store(&self, year: u16, day: u8, input: &str) -> Result<(), InputError> {
+        let path = self.path(year, day);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|source| InputError::Io {
+                path: parent.to_owned(),
+                source,
+            })?;
+        }
+        fs::write(&path, input).map_err(|source| InputError::Io { path, source })
+    }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    /// The input is not cached and could not be fetched
+    Missing {
+        year: u16,
+        day: u8,
+        path: PathBuf,
+    },
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Fetch(String),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Missing { year, day, path } => write!(
+                f,
+                "no input for {year} day {day}, expected it at {}",
+                path.display()
+            ),
+            InputError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            InputError::Fetch(message) => write!(f, "failed to fetch input: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "fetch")]
+pub use http::{HttpFetcher, SESSION_VAR};
+
+#[cfg(feature = "fetch")]
+mod http {
+    use super::{Fetch, InputError};
+
+    /// Environment variable holding the session cookie used to download inputs.
+    pub const SESSION_VAR: &str = "AOC_SESSION";
+
+    /// Downloads inputs from `<base url>/<year>/day/<N>/input`.
+    pub struct HttpFetcher {
+        base_url: String,
+        session: Option<String>,
+    }
+
+    impl HttpFetcher {
+        pub const DEFAULT_URL: &str = "https://adventofcode.com";
+
+        pub fn new(base_url: impl Into<String>) -> Self {
+            Self {
+                base_url: base_url.into().trim_end_matches('/').to_owned(),
+                session: None,
+            }
+        }
+
+        /// Reads the session cookie from `AOC_SESSION`.
+        pub fn with_env_session(mut self) -> Self {
+            self.session = std::env::var(SESSION_VAR).ok();
+            self
+        }
+
+        pub fn with_session(mut self, session: impl Into<String>) -> Self {
+            self.session = Some(session.into());
+            self
+        }
+    }
+
+    impl Fetch for HttpFetcher {
+        fn fetch(&self, year: u16, day: u8) -> Result<String, InputError> {
+            let url = format!("{}/{year}/day/{day}/input", self.base_url);
+
+            let mut request = ureq::get(&url);
+            if let Some(session) = &self.session {
+                request = request.header("Cookie", format!("session={session}"));
+            }
+
+            request
+                .call()
+                .and_then(|mut response| response.body_mut().read_to_string())
+                .map_err(|error| InputError::Fetch(format!("{url}: {error}")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    struct FakeFetcher(Rc<Cell<usize>>);
+
+    impl Fetch for FakeFetcher {
+        fn fetch(&self, year: u16, day: u8) -> Result<String, InputError> {
+            self.0.set(self.0.get() + 1);
+            Ok(format!("{year}-{day}\n"))
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-core-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let provider = InputProvider::new(temp_dir("missing"));
+
+        let error = provider.load(2025, 1).unwrap_err();
+        assert!(matches!(
+            error,
+            InputError::Missing {
+                year: 2025,
+                day: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_empty_input_is_missing() {
+        let dir = temp_dir("empty");
+        fs::create_dir_all(dir.join("2025")).unwrap();
+        fs::write(dir.join("2025/day2.txt"), "\n").unwrap();
+
+        let provider = InputProvider::new(&dir);
+        assert!(matches!(
+            provider.load(2025, 2),
+            Err(InputError::Missing { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fetch_and_cache() {
+        let dir = temp_dir("fetch");
+        let calls = Rc::new(Cell::new(0));
+        let provider = InputProvider::new(&dir).with_fetcher(FakeFetcher(calls.clone()));
+
+        assert_eq!(provider.load(2025, 3).unwrap(), "2025-3\n");
+        assert_eq!(provider.load(2025, 3).unwrap(), "2025-3\n");
+        assert_eq!(calls.get(), 1);
+        assert_eq!(
+            fs::read_to_string(dir.join("2025/day3.txt")).unwrap(),
+            "2025-3\n"
+        );
+    }
+
+    #[test]
+    fn test_named_input_is_never_fetched() {
+        let dir = temp_dir("named");
+        let calls = Rc::new(Cell::new(0));
+        let provider = InputProvider::new(&dir).with_fetcher(FakeFetcher(calls.clone()));
+
+        assert!(matches!(
+            provider.load_named(2025, 4, "alice"),
+            Err(InputError::Missing { .. })
+        ));
+        assert_eq!(calls.get(), 0);
+
+        fs::create_dir_all(dir.join("2025")).unwrap();
+        fs::write(dir.join("2025/day4-alice.txt"), "@@.\n").unwrap();
+        assert_eq!(provider.load_named(2025, 4, "alice").unwrap(), "@@.\n");
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn test_http_fetcher() {
+        use std::{
+            io::{BufRead, BufReader, Write},
+            net::TcpListener,
+            thread,
+        };
+
+        // a local stand-in for the puzzle server
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push(line.trim().to_owned());
+            }
+
+            let body = "L68\nL30\n";
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request
+        });
+
+        let fetcher = HttpFetcher::new(format!("http://{address}/")).with_session("abc");
+        let input = fetcher.fetch(2025, 1).unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(input, "L68\nL30\n");
+        assert_eq!(request[0], "GET /2025/day/1/input HTTP/1.1");
+        assert!(
+            request
+                .iter()
+                .any(|header| header.eq_ignore_ascii_case("cookie: session=abc"))
+        );
+    }
+}
@@ -0,0 +1,15 @@
+mod answer;
+pub mod automaton;
+mod error;
+pub mod grid;
+pub mod input;
+mod registry;
+mod solution;
+pub mod visualise;
+
+pub use answer::Answer;
+pub use error::ParseError;
+pub use grid::Grid;
+pub use input::{InputError, InputProvider};
+pub use registry::{AnswerRegistry, Mismatch, RegisteredAnswers, RegistryError};
+pub use solution::Solution;
@@ -0,0 +1,260 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::PathBuf, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::Answer;
+
+/// Known answers read from an `answers.toml` file, keyed by year, day and input name:
+///
+/// ```toml
+/// [2025.1.input]
+/// part1 = 1150
+/// part2 = 6738
+/// ```
+///
+/// Either part may be left out when its answer is not known (yet).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AnswerRegistry {
+    entries: Vec<RegisteredAnswers>,
+}
+
+/// The expected answers for a single input of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredAnswers {
+    pub year: u16,
+    pub day: u8,
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawAnswers {
+    part1: Option<toml::Value>,
+    part2: Option<toml::Value>,
+}
+
+type RawRegistry = BTreeMap<String, BTreeMap<String, BTreeMap<String, RawAnswers>>>;
+
+impl AnswerRegistry {
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, RegistryError> {
+        let path = path.into();
+        match fs::read_to_string(&path) {
+            Ok(registry) => registry.parse(),
+            Err(source) => Err(RegistryError::Io { path, source }),
+        }
+    }
+
+    pub fn entries(&self) -> &[RegisteredAnswers] {
+        &self.entries
+    }
+
+    pub fn year(&self, year: u16) -> impl Iterator<Item = &RegisteredAnswers> {
+        self.entries.iter().filter(move |entry| entry.year == year)
+    }
+
+    pub fn get(&self, year: u16, day: u8, input: &str) -> Option<&RegisteredAnswers> {
+        self.entries
+            .iter()
+            .find(|entry| (entry.year, entry.day, entry.input.as_str()) == (year, day, input))
+    }
+}
+
+impl FromStr for AnswerRegistry {
+    type Err = RegistryError;
+
+    fn from_str(registry: &str) -> Result<Self, Self::Err> {
+        let raw: RawRegistry = toml::from_str(registry).map_err(RegistryError::Toml)?;
+
+        let mut entries = Vec::new();
+        for (year_key, days) in raw {
+            let year = year_key
+                .parse()
+                .map_err(|_| RegistryError::InvalidKey(year_key.clone()))?;
+            for (day_key, inputs) in days {
+                let day = day_key
+                    .parse()
+                    .ok()
+                    .filter(|day| (1..=25).contains(day))
+                    .ok_or_else(|| RegistryError::InvalidKey(format!("{year_key}.{day_key}")))?;
+                for (input, answers) in inputs {
+                    entries.push(RegisteredAnswers {
+                        year,
+                        day,
+                        input,
+                        part1: answers.part1.map(expected_answer),
+                        part2: answers.part2.map(expected_answer),
+                    });
+                }
+            }
+        }
+        entries.sort_by_key(|entry| entry.day);
+        entries.sort_by_key(|entry| entry.year);
+
+        Ok(Self { entries })
+    }
+}
+
+// answers are compared by their printed form, so `123` and `"123"` register the same answer
+fn expected_answer(value: toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text,
+        toml::Value::Float(float) => Answer::Float(float).to_string(),
+        value => value.to_string(),
+    }
+}
+
+impl RegisteredAnswers {
+    /// Compares the computed answers against the registered ones, parts without an entry are skipped.
+    pub fn check(&self, (part1, part2): &(Answer, Answer)) -> Vec<Mismatch> {
+        [(1, &self.part1, part1), (2, &self.part2, part2)]
+            .into_iter()
+            .filter_map(|(part, expected, actual)| {
+                let expected = expected.as_ref()?;
+                let actual = actual.to_string();
+                (*expected != actual).then(|| Mismatch {
+                    year: self.year,
+                    day: self.day,
+                    input: self.input.clone(),
+                    part,
+                    expected: expected.clone(),
+                    actual,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A computed answer that differs from the registered one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub year: u16,
+    pub day: u8,
+    pub input: String,
+    pub part: u8,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} day {} ({}) part {}: expected {}, got {}",
+            self.year, self.day, self.input, self.part, self.expected, self.actual
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum RegistryError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Toml(toml::de::Error),
+    /// A year or day key that is not a number in range
+    InvalidKey(String),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            RegistryError::Toml(error) => write!(f, "invalid answer registry: {error}"),
+            RegistryError::InvalidKey(key) => {
+                write!(
+                    f,
+                    "invalid answer registry key `{key}`, expected <year>.<day>"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RegistryError::Io { source, .. } => Some(source),
+            RegistryError::Toml(error) => Some(error),
+            RegistryError::InvalidKey(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGISTRY: &str = r#"
+[2025.10.input]
+part1 = 486
+
+[2025.1.input]
+part1 = 1150
+part2 = "6738"
+
+[2025.1.alice]
+part2 = 42
+
+[2024.25.input]
+part1 = "abc"
+"#;
+
+    #[test]
+    fn test_parse() {
+        let registry: AnswerRegistry = REGISTRY.parse().unwrap();
+
+        let keys: Vec<_> = registry
+            .entries()
+            .iter()
+            .map(|entry| (entry.year, entry.day, entry.input.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                (2024, 25, "input"),
+                (2025, 1, "alice"),
+                (2025, 1, "input"),
+                (2025, 10, "input")
+            ]
+        );
+        assert_eq!(registry.year(2025).count(), 3);
+
+        let day1 = registry.get(2025, 1, "input").unwrap();
+        assert_eq!(day1.part1.as_deref(), Some("1150"));
+        assert_eq!(day1.part2.as_deref(), Some("6738"));
+    }
+
+    #[test]
+    fn test_check() {
+        let registry: AnswerRegistry = REGISTRY.parse().unwrap();
+        let day1 = registry.get(2025, 1, "input").unwrap();
+
+        assert!(day1.check(&(1150u64.into(), 6738u64.into())).is_empty());
+
+        let mismatches = day1.check(&(1150u64.into(), Answer::Unsolved));
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "2025 day 1 (input) part 2: expected 6738, got -"
+        );
+
+        // parts without a registered answer are never checked
+        let alice = registry.get(2025, 1, "alice").unwrap();
+        assert!(alice.check(&(Answer::Unsolved, 42u64.into())).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_registry() {
+        assert!(matches!(
+            "[2025.26.input]\npart1 = 1".parse::<AnswerRegistry>(),
+            Err(RegistryError::InvalidKey(key)) if key == "2025.26"
+        ));
+        assert!(matches!(
+            "[2025.1.input]\npart3 = 1".parse::<AnswerRegistry>(),
+            Err(RegistryError::Toml(_))
+        ));
+    }
+}
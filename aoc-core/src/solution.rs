@@ -0,0 +1,22 @@
+use crate::{Answer, ParseError};
+
+/// A single day of a puzzle year, split into a shared parse step and the two parts.
+pub trait Solution {
+    const DAY: u8;
+
+    /// The parsed puzzle input, may borrow from the raw input
+    type Parsed<'a>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError>;
+
+    fn part1(parsed: &Self::Parsed<'_>) -> Answer;
+
+    fn part2(parsed: &Self::Parsed<'_>) -> Answer;
+
+    /// Parses `input` once and solves both parts with it.
+    fn solve(input: &str) -> Result<(Answer, Answer), ParseError> {
+        let parsed = Self::parse(input)?;
+
+        Ok((Self::part1(&parsed), Self::part2(&parsed)))
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    fmt, fs,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{Grid, ParseError};
+
+pub type Rgb = [u8; 3];
+
+pub const WHITE: Rgb = [255, 255, 255];
+pub const BLACK: Rgb = [0, 0, 0];
+
+/// A single image, rendered from a grid with every cell as a square of pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Frame {
+    pub fn from_grid<T>(grid: &Grid<T>, scale: usize, mut colour: impl FnMut(&T) -> Rgb) -> Self {
+        let (width, height) = (grid.columns() * scale, grid.rows() * scale);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for row in grid.row_iter() {
+            let colours: Vec<_> = row.iter().map(&mut colour).collect();
+            for _ in 0..scale {
+                for colour in &colours {
+                    pixels.extend(std::iter::repeat_n(*colour, scale));
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.pixels.iter().flatten().copied().collect()
+    }
+
+    /// Writes the frame as a binary (P6) PPM image.
+    pub fn write_ppm(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.bytes())
+    }
+
+    #[cfg(feature = "png")]
+    pub fn write_png(&self, out: impl Write) -> io::Result<()> {
+        let mut writer = png_encoder(out, self.width, self.height)?
+            .write_header()
+            .map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.bytes())
+            .map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+}
+
+#[cfg(feature = "png")]
+fn png_encoder<W: Write>(
+    out: W,
+    width: usize,
+    height: usize,
+) -> io::Result<png::Encoder<'static, W>> {
+    let size = |pixels: usize| u32::try_from(pixels).map_err(io::Error::other);
+    let mut encoder = png::Encoder::new(out, size(width)?, size(height)?);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    Ok(encoder)
+}
+
+/// Writes every frame of `frames` into a single animated PNG, showing each for `delay_ms`.
+#[cfg(feature = "png")]
+pub fn write_animation(frames: &[Frame], delay_ms: u16, out: impl Write) -> io::Result<()> {
+    let Some(first) = frames.first() else {
+        return Ok(());
+    };
+
+    let mut encoder = png_encoder(out, first.width, first.height)?;
+    let count = u32::try_from(frames.len()).map_err(io::Error::other)?;
+    encoder.set_animated(count, 0).map_err(io::Error::other)?;
+    encoder
+        .set_frame_delay(delay_ms, 1000)
+        .map_err(io::Error::other)?;
+
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    for frame in frames {
+        writer
+            .write_image_data(&frame.bytes())
+            .map_err(io::Error::other)?;
+    }
+    writer.finish().map_err(io::Error::other)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFormat {
+    /// One `frame-<N>.ppm` per frame
+    #[default]
+    Ppm,
+    /// One `frame-<N>.png` per frame
+    #[cfg(feature = "png")]
+    Png,
+    /// All frames in a single `animation.png`
+    #[cfg(feature = "png")]
+    AnimatedPng,
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "ppm" => Ok(ImageFormat::Ppm),
+            #[cfg(feature = "png")]
+            "png" => Ok(ImageFormat::Png),
+            #[cfg(feature = "png")]
+            "apng" => Ok(ImageFormat::AnimatedPng),
+            format => Err(format!("unsupported image format `{format}`")),
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ImageFormat::Ppm => "ppm",
+            #[cfg(feature = "png")]
+            ImageFormat::Png => "png",
+            #[cfg(feature = "png")]
+            ImageFormat::AnimatedPng => "apng",
+        })
+    }
+}
+
+/// Renders grid states into numbered image frames in a directory, one call per iteration.
+pub struct FrameWriter {
+    dir: PathBuf,
+    format: ImageFormat,
+    scale: usize,
+    count: usize,
+    #[cfg(feature = "png")]
+    animation: Vec<Frame>,
+}
+
+impl FrameWriter {
+    pub const DEFAULT_SCALE: usize = 4;
+
+    /// Delay between the frames of an animated PNG.
+    pub const FRAME_DELAY_MS: u16 = 100;
+
+    pub fn new(dir: impl Into<PathBuf>, format: ImageFormat) -> Self {
+        Self {
+            dir: dir.into(),
+            format,
+            scale: Self::DEFAULT_SCALE,
+            count: 0,
+            #[cfg(feature = "png")]
+            animation: Vec::new(),
+        }
+    }
+
+    /// Sets the size in pixels of a single grid cell.
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The number of frames written so far.
+    pub fn frames(&self) -> usize {
+        self.count
+    }
+
+    /// Renders `grid` as the next frame.
+    pub fn write<T>(&mut self, grid: &Grid<T>, colour: impl FnMut(&T) -> Rgb) -> io::Result<()> {
+        let frame = Frame::from_grid(grid, self.scale, colour);
+        self.count += 1;
+
+        let path = |extension: &str| {
+            self.dir
+                .join(format!("frame-{:04}.{extension}", self.count))
+        };
+        match self.format {
+            ImageFormat::Ppm => frame.write_ppm(self.create(&path("ppm"))?),
+            #[cfg(feature = "png")]
+            ImageFormat::Png => frame.write_png(self.create(&path("png"))?),
+            #[cfg(feature = "png")]
+            ImageFormat::AnimatedPng => {
+                self.animation.push(frame);
+                Ok(())
+            }
+        }
+    }
+
+    /// Writes out frames that are only kept until the end, i.e. the animation.
+    pub fn finish(self) -> io::Result<()> {
+        #[cfg(feature = "png")]
+        if self.format == ImageFormat::AnimatedPng {
+            let out = self.create(&self.dir.join("animation.png"))?;
+            write_animation(&self.animation, Self::FRAME_DELAY_MS, out)?;
+        }
+
+        Ok(())
+    }
+
+    fn create(&self, path: &Path) -> io::Result<BufWriter<fs::File>> {
+        fs::create_dir_all(&self.dir)?;
+        Ok(BufWriter::new(fs::File::create(path)?))
+    }
+}
+
+/// Failure to visualise a day, either its input or writing the frames.
+#[derive(Debug)]
+pub enum VisualiseError {
+    Parse(ParseError),
+    Io(io::Error),
+    /// The grid has too many cells to render a frame of
+    TooLarge {
+        rows: usize,
+        columns: usize,
+    },
+}
+
+impl From<ParseError> for VisualiseError {
+    fn from(error: ParseError) -> Self {
+        VisualiseError::Parse(error)
+    }
+}
+
+impl From<io::Error> for VisualiseError {
+    fn from(error: io::Error) -> Self {
+        VisualiseError::Io(error)
+    }
+}
+
+impl fmt::Display for VisualiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VisualiseError::Parse(error) => error.fmt(f),
+            VisualiseError::Io(error) => write!(f, "failed to write frame: {error}"),
+            VisualiseError::TooLarge { rows, columns } => {
+                write!(f, "a {rows}x{columns} grid is too large to render")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VisualiseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VisualiseError::Parse(error) => Some(error),
+            VisualiseError::Io(error) => Some(error),
+            VisualiseError::TooLarge { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn grid() -> Grid<bool> {
+        Grid::from_rows(vec![vec![true, false], vec![false, false]]).unwrap()
+    }
+
+    fn colour(cell: &bool) -> Rgb {
+        if *cell { BLACK } else { WHITE }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-core-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_ppm() {
+        let frame = Frame::from_grid(&grid(), 2, colour);
+        assert_eq!((frame.width(), frame.height()), (4, 4));
+
+        let mut ppm = Vec::new();
+        frame.write_ppm(&mut ppm).unwrap();
+
+        let (header, pixels) = ppm.split_at(b"P6\n4 4\n255\n".len());
+        assert_eq!(header, b"P6\n4 4\n255\n");
+        assert_eq!(pixels.len(), 4 * 4 * 3);
+        // the top left cell covers 2x2 pixels
+        assert_eq!(&pixels[..6], [0; 6]);
+        assert_eq!(&pixels[6..12], [255; 6]);
+        assert_eq!(&pixels[12..18], [0; 6]);
+        assert_eq!(&pixels[24..], [255; 24]);
+    }
+
+    #[test]
+    fn test_frame_writer() {
+        let dir = temp_dir("frames");
+        let mut frames = FrameWriter::new(&dir, ImageFormat::Ppm).with_scale(1);
+
+        frames.write(&grid(), colour).unwrap();
+        frames.write(&grid(), colour).unwrap();
+        frames.finish().unwrap();
+
+        assert!(dir.join("frame-0001.ppm").exists());
+        assert!(dir.join("frame-0002.ppm").exists());
+    }
+
+    #[test]
+    fn test_image_format() {
+        assert_eq!("ppm".parse(), Ok(ImageFormat::Ppm));
+        assert!("gif".parse::<ImageFormat>().is_err());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_animation() {
+        let dir = temp_dir("animation");
+        let mut frames = FrameWriter::new(&dir, ImageFormat::AnimatedPng);
+
+        frames.write(&grid(), colour).unwrap();
+        frames.write(&grid(), |cell| colour(&!cell)).unwrap();
+        assert_eq!(frames.frames(), 2);
+        frames.finish().unwrap();
+
+        let png = fs::read(dir.join("animation.png")).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert!(png.windows(4).any(|chunk| chunk == b"acTL"));
+    }
+}
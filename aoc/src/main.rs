@@ -0,0 +1,230 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use aoc_core::{
+    Answer, AnswerRegistry, InputError, InputProvider,
+    input::{HttpFetcher, INPUT_DIR_VAR},
+    visualise::{FrameWriter, ImageFormat},
+};
+use clap::{Parser, Subcommand};
+
+mod scaffold;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Advent of Code solution runner")]
+struct Cli {
+    /// Input cache, inputs are stored as `<year>/day<N>.txt`
+    #[arg(long, global = true, env = INPUT_DIR_VAR, default_value = "inputs")]
+    input_dir: PathBuf,
+    /// Download inputs missing from the cache, the session cookie is read from `AOC_SESSION`
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        num_args = 0..=1,
+        default_missing_value = HttpFetcher::DEFAULT_URL
+    )]
+    fetch: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run both parts of a single day
+    Run {
+        year: u16,
+        day: u8,
+        /// Puzzle input file or `-` for stdin, loads the input from the cache when omitted
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Write the frames of a day's visualisation as images
+    Visualise {
+        year: u16,
+        day: u8,
+        /// Puzzle input file or `-` for stdin, loads the input from the cache when omitted
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        /// Directory to write the frames to
+        #[arg(short, long, default_value = "frames")]
+        out: PathBuf,
+        /// `ppm` or `png` for one image per frame, `apng` for a single animated PNG
+        #[arg(long, default_value_t = ImageFormat::Ppm)]
+        format: ImageFormat,
+        /// Size in pixels of a single grid cell
+        #[arg(long, default_value_t = FrameWriter::DEFAULT_SCALE)]
+        scale: usize,
+    },
+    /// Check every registered day against its known answers
+    Verify {
+        /// Only verify this year
+        year: Option<u16>,
+        /// Only verify this day
+        day: Option<u8>,
+        /// Registry of known answers, keyed by year, day and input name
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
+    /// Scaffold a new day from the template, creating the year's crate if needed
+    New {
+        year: u16,
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+        /// Registry to add an empty entry for the day to
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let inputs = input_provider(&cli);
+
+    match cli.command {
+        Command::Run { year, day, input } => {
+            let input = match input {
+                Some(path) => read_input(&path)?,
+                None => inputs.load(year, day)?,
+            };
+            let (part1, part2) = solve(year, day, &input)?;
+
+            println!("part 1: {part1}");
+            println!("part 2: {part2}");
+        }
+        Command::Visualise {
+            year,
+            day,
+            input,
+            out,
+            format,
+            scale,
+        } => {
+            let input = match input {
+                Some(path) => read_input(&path)?,
+                None => inputs.load(year, day)?,
+            };
+            let mut frames = FrameWriter::new(out, format).with_scale(scale);
+            visualise(year, day, &input, &mut frames)?;
+
+            let (count, dir) = (frames.frames(), frames.dir().to_owned());
+            frames.finish()?;
+
+            println!("wrote {count} frames to {}", dir.display());
+        }
+        Command::Verify { year, day, answers } => verify(&inputs, &answers, year, day)?,
+        Command::New { year, day, answers } => {
+            let changes = scaffold::new_day(Path::new("."), &inputs, &answers, year, day)?;
+            for path in changes.created {
+                println!("created {}", path.display());
+            }
+            for path in changes.updated {
+                println!("updated {}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn input_provider(cli: &Cli) -> InputProvider {
+    let inputs = InputProvider::new(&cli.input_dir);
+    match &cli.fetch {
+        Some(url) => inputs.with_fetcher(HttpFetcher::new(url).with_env_session()),
+        None => inputs,
+    }
+}
+
+fn read_input(path: &Path) -> anyhow::Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .context("failed to read input from stdin")?;
+        return Ok(input);
+    }
+
+    fs::read_to_string(path)
+        .with_context(|| format!("failed to read input from {}", path.display()))
+}
+
+fn verify(
+    inputs: &InputProvider,
+    answers: &Path,
+    year: Option<u16>,
+    day: Option<u8>,
+) -> anyhow::Result<()> {
+    let registry = AnswerRegistry::load(answers)?;
+
+    let mut failed = 0;
+    for expected in registry.entries() {
+        if year.is_some_and(|year| year != expected.year)
+            || day.is_some_and(|day| day != expected.day)
+        {
+            continue;
+        }
+
+        let name = format!(
+            "{} day {} ({})",
+            expected.year, expected.day, expected.input
+        );
+        let input = match inputs.load_named(expected.year, expected.day, &expected.input) {
+            Ok(input) => input,
+            Err(error @ InputError::Missing { .. }) => {
+                println!("{name}: skipped, {error}");
+                continue;
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let mismatches = match solve(expected.year, expected.day, &input) {
+            Ok(answers) => expected.check(&answers),
+            Err(error) => {
+                failed += 1;
+                println!("{name}: failed, {error}");
+                continue;
+            }
+        };
+
+        if mismatches.is_empty() {
+            println!("{name}: ok");
+        } else {
+            failed += 1;
+            for mismatch in mismatches {
+                println!("{mismatch}");
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{failed} registered inputs did not match their answers");
+    }
+
+    Ok(())
+}
+
+fn solve(year: u16, day: u8, input: &str) -> anyhow::Result<(Answer, Answer)> {
+    let answers = match year {
+        2025 => y2025::run(day, input),
+        _ => bail!("no solutions for year {year}"),
+    };
+
+    let answers = answers.with_context(|| format!("no solution for {year} day {day}"))?;
+
+    Ok(answers?)
+}
+
+fn visualise(year: u16, day: u8, input: &str, frames: &mut FrameWriter) -> anyhow::Result<()> {
+    let result = match year {
+        2025 => y2025::visualise(day, input, frames),
+        _ => bail!("no visualisations for year {year}"),
+    };
+
+    let result = result.with_context(|| format!("no visualisation for {year} day {day}"))?;
+
+    Ok(result?)
+}
@@ -0,0 +1,386 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, bail};
+use aoc_core::{AnswerRegistry, InputProvider, input::DEFAULT_INPUT};
+
+const DAY_TEMPLATE: &str = include_str!("../templates/day0.rs");
+const LIB_TEMPLATE: &str = include_str!("../templates/lib.rs");
+const MANIFEST_TEMPLATE: &str = include_str!("../templates/Cargo.toml.in");
+
+const DAY_FALLBACK_ARM: &str = "_ => return None,";
+const YEAR_FALLBACK_ARM: &str = "_ => bail!(\"no solutions for year {year}\"),";
+
+/// Files written by the scaffolding.
+#[derive(Debug, Default)]
+pub struct Changes {
+    pub created: Vec<PathBuf>,
+    pub updated: Vec<PathBuf>,
+}
+
+/// Adds `day` to the `y<year>` crate in `workspace`, creating the crate first if needed.
+///
+/// Besides the module this registers the day in the crate's `run`, adds an empty input placeholder
+/// and an (empty) entry to the answer registry.
+pub fn new_day(
+    workspace: &Path,
+    inputs: &InputProvider,
+    answers: &Path,
+    year: u16,
+    day: u8,
+) -> anyhow::Result<Changes> {
+    let mut changes = Changes::default();
+
+    let crate_dir = workspace.join(format!("y{year}"));
+    if !crate_dir.exists() {
+        new_year(workspace, year, &mut changes)?;
+    }
+
+    let module = crate_dir.join(format!("src/day{day}.rs"));
+    if module.exists() {
+        bail!("{} already exists", module.display());
+    }
+    create(&module, &render_day(day), &mut changes)?;
+
+    let lib = crate_dir.join("src/lib.rs");
+    update(&lib, &mut changes, |source| add_day_to_lib(source, day))?;
+
+    let input = inputs.path(year, day);
+    if !input.exists() {
+        create(&input, "", &mut changes)?;
+    }
+
+    let registry = match fs::read_to_string(answers) {
+        Ok(registry) => registry,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error).context(format!("failed to read {}", answers.display())),
+    };
+    let registered = registry
+        .parse::<AnswerRegistry>()
+        .with_context(|| answers.display().to_string())?
+        .get(year, day, DEFAULT_INPUT)
+        .is_some();
+    if !registered {
+        let updated = format!("{registry}{}", registry_entry(&registry, year, day));
+        if registry.is_empty() {
+            create(answers, &updated, &mut changes)?;
+        } else {
+            update(answers, &mut changes, |_| Ok(updated))?;
+        }
+    }
+
+    Ok(changes)
+}
+
+// a new year is its own workspace member, picked up by the `y*` members glob
+fn new_year(workspace: &Path, year: u16, changes: &mut Changes) -> anyhow::Result<()> {
+    let crate_dir = workspace.join(format!("y{year}"));
+    create(
+        &crate_dir.join("Cargo.toml"),
+        &render_manifest(year),
+        changes,
+    )?;
+    create(&crate_dir.join("src/lib.rs"), &render_lib(year), changes)?;
+
+    let runner = workspace.join("aoc");
+    update(&runner.join("Cargo.toml"), changes, |manifest| {
+        add_year_dependency(manifest, year)
+    })?;
+    update(&runner.join("src/main.rs"), changes, |main| {
+        add_year_to_runner(main, year)
+    })?;
+
+    Ok(())
+}
+
+fn create(path: &Path, contents: &str, changes: &mut Changes) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    changes.created.push(path.to_owned());
+    Ok(())
+}
+
+fn update(
+    path: &Path,
+    changes: &mut Changes,
+    edit: impl FnOnce(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<()> {
+    let source =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let updated = edit(&source).with_context(|| format!("failed to update {}", path.display()))?;
+    fs::write(path, updated).with_context(|| format!("failed to write {}", path.display()))?;
+    changes.updated.push(path.to_owned());
+    Ok(())
+}
+
+fn render_day(day: u8) -> String {
+    DAY_TEMPLATE
+        .replace("const DAY: u8 = 0;", &format!("const DAY: u8 = {day};"))
+        .replace("Day0", &format!("Day{day}"))
+        .replace("day0", &format!("day{day}"))
+}
+
+fn render_lib(year: u16) -> String {
+    LIB_TEMPLATE.replace("YEAR: u16 = 0;", &format!("YEAR: u16 = {year};"))
+}
+
+fn render_manifest(year: u16) -> String {
+    MANIFEST_TEMPLATE.replace("name = \"y0\"", &format!("name = \"y{year}\""))
+}
+
+fn registry_entry(registry: &str, year: u16, day: u8) -> String {
+    let separator = match registry {
+        "" => "",
+        registry if registry.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    format!("{separator}[{year}.{day}.{DEFAULT_INPUT}]\n# part1 =\n# part2 =\n")
+}
+
+/// Declares the `day` module and dispatches to it from `run`.
+fn add_day_to_lib(lib: &str, day: u8) -> anyhow::Result<String> {
+    let mut lines: Vec<String> = lib.lines().map(str::to_owned).collect();
+
+    let module = format!("pub mod day{day};");
+    if lines.contains(&module) {
+        bail!("day {day} is already declared");
+    }
+    match lines.iter().rposition(|line| line.starts_with("pub mod ")) {
+        // same order as rustfmt sorts the declarations
+        Some(last) => {
+            let first = lines
+                .iter()
+                .position(|line| line.starts_with("pub mod "))
+                .unwrap_or(last);
+            lines.insert(last + 1, module);
+            lines[first..=last + 1].sort_by_key(|line| line.trim_end_matches(';').to_owned());
+        }
+        None => {
+            lines.insert(0, String::new());
+            lines.insert(0, module);
+        }
+    }
+
+    let arm = format!("{day} => day{day}::Day{day}::solve(input),");
+    insert_match_arm(&mut lines, day.into(), arm, DAY_FALLBACK_ARM)?;
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Dispatches `year` from the runner's `solve`.
+fn add_year_to_runner(main: &str, year: u16) -> anyhow::Result<String> {
+    let mut lines: Vec<String> = main.lines().map(str::to_owned).collect();
+
+    let arm = format!("{year} => y{year}::run(day, input),");
+    insert_match_arm(&mut lines, year.into(), arm, YEAR_FALLBACK_ARM)?;
+
+    Ok(lines.join("\n") + "\n")
+}
+
+// keeps the numbered arms in front of `fallback` sorted
+fn insert_match_arm(
+    lines: &mut Vec<String>,
+    number: u32,
+    arm: String,
+    fallback: &str,
+) -> anyhow::Result<()> {
+    let Some(fallback_idx) = lines.iter().position(|line| line.trim() == fallback) else {
+        bail!("no `{fallback}` match arm to add {number} in front of");
+    };
+
+    let numbered_arm = |line: &str| {
+        line.trim()
+            .split_once(" => ")
+            .and_then(|(pattern, _)| pattern.parse::<u32>().ok())
+    };
+    let mut idx = fallback_idx;
+    while idx > 0 {
+        match numbered_arm(&lines[idx - 1]) {
+            Some(existing) if existing == number => bail!("{number} is already dispatched"),
+            Some(existing) if existing > number => idx -= 1,
+            _ => break,
+        }
+    }
+
+    let indent = &lines[fallback_idx][..lines[fallback_idx].len() - fallback.len()];
+    lines.insert(idx, format!("{indent}{arm}"));
+    Ok(())
+}
+
+/// Adds the `y<year>` crate to the runner's dependencies.
+fn add_year_dependency(manifest: &str, year: u16) -> anyhow::Result<String> {
+    let mut lines: Vec<String> = manifest.lines().map(str::to_owned).collect();
+
+    let Some(section) = lines.iter().position(|line| line == "[dependencies]") else {
+        bail!("no [dependencies] section");
+    };
+    let end = lines[section + 1..]
+        .iter()
+        .position(|line| line.trim().is_empty() || line.starts_with('['))
+        .map_or(lines.len(), |idx| section + 1 + idx);
+
+    lines.insert(end, format!("y{year} = {{ path = \"../y{year}\" }}"));
+    lines[section + 1..=end].sort();
+
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("aoc-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_render_day() {
+        let module = render_day(13);
+
+        assert!(module.contains("pub struct Day13;"));
+        assert!(module.contains("const DAY: u8 = 13;"));
+        assert!(module.contains("use crate::day13::parsing::parse_input;"));
+        assert!(!module.contains("day0") && !module.contains("Day0"));
+    }
+
+    #[test]
+    fn test_add_day_to_lib() {
+        let lib = "\
+pub mod day1;
+pub mod day2;
+pub mod day9;
+
+pub fn run(day: u8, input: &str) -> Option<Result<(Answer, Answer), ParseError>> {
+    let answers = match day {
+        1 => day1::Day1::solve(input),
+        2 => day2::Day2::solve(input),
+        9 => day9::Day9::solve(input),
+        _ => return None,
+    };
+
+    Some(answers)
+}
+";
+        let updated = add_day_to_lib(lib, 10).unwrap();
+
+        assert_eq!(
+            updated,
+            "\
+pub mod day1;
+pub mod day10;
+pub mod day2;
+pub mod day9;
+
+pub fn run(day: u8, input: &str) -> Option<Result<(Answer, Answer), ParseError>> {
+    let answers = match day {
+        1 => day1::Day1::solve(input),
+        2 => day2::Day2::solve(input),
+        9 => day9::Day9::solve(input),
+        10 => day10::Day10::solve(input),
+        _ => return None,
+    };
+
+    Some(answers)
+}
+"
+        );
+        assert!(add_day_to_lib(&updated, 10).is_err());
+
+        let updated = add_day_to_lib(&updated, 3).unwrap();
+        assert!(updated.contains(
+            "        2 => day2::Day2::solve(input),\n        3 => day3::Day3::solve(input),\n"
+        ));
+    }
+
+    #[test]
+    fn test_add_day_to_new_lib() {
+        let lib = add_day_to_lib(&render_lib(2026), 1).unwrap();
+
+        assert!(lib.starts_with("pub mod day1;\n\npub use aoc_core::ParseError;"));
+        assert!(lib.contains("pub const YEAR: u16 = 2026;"));
+        assert!(lib.contains("        1 => day1::Day1::solve(input),\n        _ => return None,"));
+    }
+
+    #[test]
+    fn test_add_year_to_runner() {
+        let main = add_year_to_runner(include_str!("main.rs"), 2026).unwrap();
+
+        assert!(main.contains(
+            "        2025 => y2025::run(day, input),\n        2026 => y2026::run(day, input),\n"
+        ));
+        assert!(add_year_to_runner(&main, 2026).is_err());
+    }
+
+    #[test]
+    fn test_add_year_dependency() {
+        let manifest = add_year_dependency(include_str!("../Cargo.toml"), 2024).unwrap();
+
+        let dependencies: Vec<_> = manifest
+            .lines()
+            .skip_while(|line| *line != "[dependencies]")
+            .filter(|line| line.starts_with('y'))
+            .collect();
+        assert_eq!(
+            dependencies,
+            [
+                "y2024 = { path = \"../y2024\" }",
+                "y2025 = { path = \"../y2025\" }"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_year() {
+        let workspace = temp_dir("new-year");
+        fs::create_dir_all(workspace.join("aoc/src")).unwrap();
+        fs::write(
+            workspace.join("aoc/Cargo.toml"),
+            include_str!("../Cargo.toml"),
+        )
+        .unwrap();
+        fs::write(workspace.join("aoc/src/main.rs"), include_str!("main.rs")).unwrap();
+        fs::write(
+            workspace.join("answers.toml"),
+            "[2025.1.input]\npart1 = 1\n",
+        )
+        .unwrap();
+
+        let inputs = InputProvider::new(workspace.join("inputs"));
+        let answers = workspace.join("answers.toml");
+        let changes = new_day(&workspace, &inputs, &answers, 2026, 1).unwrap();
+
+        assert_eq!(
+            changes.created,
+            [
+                workspace.join("y2026/Cargo.toml"),
+                workspace.join("y2026/src/lib.rs"),
+                workspace.join("y2026/src/day1.rs"),
+                workspace.join("inputs/2026/day1.txt"),
+            ]
+        );
+        assert_eq!(fs::read_to_string(inputs.path(2026, 1)).unwrap(), "");
+
+        let registry: AnswerRegistry = fs::read_to_string(&answers).unwrap().parse().unwrap();
+        let entry = registry.get(2026, 1, DEFAULT_INPUT).unwrap();
+        assert_eq!((&entry.part1, &entry.part2), (&None, &None));
+        assert!(registry.get(2025, 1, DEFAULT_INPUT).is_some());
+
+        // the day is only scaffolded once
+        assert!(new_day(&workspace, &inputs, &answers, 2026, 1).is_err());
+
+        let changes = new_day(&workspace, &inputs, &answers, 2026, 2).unwrap();
+        assert_eq!(
+            changes.updated,
+            [workspace.join("y2026/src/lib.rs"), answers]
+        );
+    }
+}
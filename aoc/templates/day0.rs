@@ -0,0 +1,73 @@
+use aoc_core::{Answer, ParseError, Solution};
+use winnow::Parser;
+
+use crate::day0::parsing::parse_input;
+
+pub struct Day0;
+
+impl Solution for Day0 {
+    const DAY: u8 = 0;
+
+    type Parsed<'a> = Vec<&'a str>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_input
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(_lines: &Self::Parsed<'_>) -> Answer {
+        Answer::Unsolved
+    }
+
+    fn part2(_lines: &Self::Parsed<'_>) -> Answer {
+        Answer::Unsolved
+    }
+}
+
+mod parsing {
+    use winnow::{
+        Parser,
+        ascii::{multispace0, till_line_ending},
+        combinator::{cut_err, eof, preceded, repeat_till, terminated},
+        error::StrContext,
+    };
+
+    pub fn parse_input<'a>(input: &mut &'a str) -> winnow::ModalResult<Vec<&'a str>> {
+        preceded(
+            multispace0,
+            repeat_till(
+                0..,
+                terminated(
+                    cut_err(till_line_ending.context(StrContext::Label("line"))),
+                    multispace0,
+                ),
+                eof,
+            ),
+        )
+        .map(|(lines, _)| lines)
+        .parse_next(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+";
+
+    #[test]
+    fn test_empty_input() {
+        let lines = Day0::parse("").unwrap();
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn test_example_input() {
+        let result = Day0::solve(EXAMPLE).unwrap();
+        assert_eq!(result, (Answer::Unsolved, Answer::Unsolved));
+    }
+}
@@ -0,0 +1,51 @@
+pub use aoc_core::ParseError;
+
+use aoc_core::{Answer, Solution};
+
+pub const YEAR: u16 = 0;
+
+/// Runs both parts of `day` against `input`.
+///
+/// Returns `None` if there is no solution for `day`.
+pub fn run(day: u8, input: &str) -> Option<Result<(Answer, Answer), ParseError>> {
+    let answers = match day {
+        _ => return None,
+    };
+
+    Some(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    // real inputs are not available everywhere, so registered answers without an input are skipped
+    #[test]
+    fn test_registered_answers() {
+        use aoc_core::{AnswerRegistry, InputProvider};
+
+        let workspace = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+        let registry = AnswerRegistry::load(format!("{workspace}/answers.toml")).unwrap();
+        let inputs = InputProvider::from_env(format!("{workspace}/inputs"));
+
+        let mut mismatches = Vec::new();
+        for expected in registry.year(YEAR) {
+            let input = match inputs.load_cached_named(YEAR, expected.day, &expected.input) {
+                Ok(input) => input,
+                Err(error) => {
+                    eprintln!("skipping: {error}");
+                    continue;
+                }
+            };
+            let answers = run(expected.day, &input)
+                .unwrap_or_else(|| panic!("no solution for registered day {}", expected.day))
+                .unwrap();
+            mismatches.extend(expected.check(&answers));
+        }
+
+        let report: Vec<_> = mismatches.iter().map(ToString::to_string).collect();
+        assert!(mismatches.is_empty(), "{}", report.join("\n"));
+    }
+}
@@ -0,0 +1,41 @@
+//! Times day 4 on a generated 10k x 10k sheet with 70% paper, the size the removal rounds have to
+//! handle in well under a second.
+
+use std::hint::black_box;
+
+use aoc_core::Solution;
+use criterion::{Criterion, criterion_group, criterion_main};
+use y2025::day4::Day4;
+
+const SIDE: usize = 10_000;
+const DENSITY: u64 = 70;
+
+fn generated() -> String {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut input = String::with_capacity((SIDE + 1) * SIDE);
+    for _ in 0..SIDE {
+        for _ in 0..SIDE {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            input.push(if state % 100 < DENSITY { '@' } else { '.' });
+        }
+        input.push('\n');
+    }
+    input
+}
+
+fn removal(c: &mut Criterion) {
+    let input = generated();
+    let paper = Day4::parse(&input).unwrap();
+
+    let mut group = c.benchmark_group("day4/10k");
+    group.sample_size(10);
+    group.bench_function("parse", |b| b.iter(|| Day4::parse(black_box(&input))));
+    group.bench_function("part1", |b| b.iter(|| Day4::part1(black_box(&paper))));
+    group.bench_function("part2", |b| b.iter(|| Day4::part2(black_box(&paper))));
+    group.finish();
+}
+
+criterion_group!(benches, removal);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+//! Compares the two ways of counting the fresh items of a day 5 inventory, looking every item up
+//! in the ranges against sorting the inventory and walking it alongside them, to tune
+//! `MERGE_JOIN_MIN`. Both include building the ingredients, so the merge-join pays for its sort.
+
+use std::{hint::black_box, ops::RangeInclusive};
+
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use y2025::day5::{Counting, Ingredients};
+
+const RANGES: usize = 200;
+const SIZES: [usize; 9] = [
+    1 << 4,
+    1 << 6,
+    1 << 8,
+    1 << 10,
+    1 << 12,
+    1 << 14,
+    1 << 16,
+    1 << 20,
+    1 << 24,
+];
+
+// about as many fresh ranges as the real input, spread over the same IDs as the unsorted inventory
+fn generated(items: usize) -> (Vec<RangeInclusive<u64>>, Vec<u64>) {
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = move |max: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % max
+    };
+
+    const IDS: u64 = 1 << 48;
+    let ranges = (0..RANGES)
+        .map(|_| {
+            let start = next(IDS);
+            start..=start + next(IDS / RANGES as u64)
+        })
+        .collect();
+    let inventory = (0..items).map(|_| next(IDS)).collect();
+
+    (ranges, inventory)
+}
+
+fn counting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day5/count_fresh");
+    group.sample_size(10);
+
+    for items in SIZES {
+        let input = generated(items);
+        for (name, counting) in [
+            ("lookup", Counting::Lookup),
+            ("merge_join", Counting::MergeJoin),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, items), &input, |b, input| {
+                b.iter_batched(
+                    || input.clone(),
+                    |(ranges, inventory)| {
+                        Ingredients::with_counting(ranges, black_box(inventory), counting)
+                            .count_fresh()
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, counting);
+criterion_main!(benches);
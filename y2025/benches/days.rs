@@ -0,0 +1,51 @@
+//! Times the parse step and each part of every day, for the example and the real input.
+//!
+//! Inputs are read from the input cache (`AOC_INPUT_DIR`, defaulting to `inputs/`), days without an
+//! input are skipped. Criterion keeps the results as JSON under `target/criterion/day<N>_<input>/`,
+//! compare commits with `cargo bench -p y2025 -- --save-baseline <name>` and `--baseline <name>`.
+
+use std::hint::black_box;
+
+use aoc_core::{InputProvider, Solution};
+use criterion::{Criterion, criterion_group, criterion_main};
+use y2025::{YEAR, day1, day2, day3, day4, day5, day6, day7, day8, day9, day10, day11, day12};
+
+const INPUTS: [&str; 2] = ["example", "input"];
+
+fn bench_day<S: Solution>(c: &mut Criterion) {
+    let inputs = InputProvider::from_env(concat!(env!("CARGO_MANIFEST_DIR"), "/../inputs"));
+
+    for name in INPUTS {
+        let input = match inputs.load_cached_named(YEAR, S::DAY, name) {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("skipping: {error}");
+                continue;
+            }
+        };
+        let parsed = S::parse(&input).unwrap();
+
+        let mut group = c.benchmark_group(format!("day{}/{name}", S::DAY));
+        group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+        group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&parsed))));
+        group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&parsed))));
+        group.finish();
+    }
+}
+
+criterion_group!(
+    days,
+    bench_day::<day1::Day1>,
+    bench_day::<day2::Day2>,
+    bench_day::<day3::Day3>,
+    bench_day::<day4::Day4>,
+    bench_day::<day5::Day5>,
+    bench_day::<day6::Day6>,
+    bench_day::<day7::Day7>,
+    bench_day::<day8::Day8>,
+    bench_day::<day9::Day9>,
+    bench_day::<day10::Day10>,
+    bench_day::<day11::Day11>,
+    bench_day::<day12::Day12>,
+);
+criterion_main!(days);
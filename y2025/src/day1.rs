@@ -1,63 +1,406 @@
+use std::{fmt, io::Write};
+
+use aoc_core::{Answer, ParseError, Solution};
+use serde::{Serialize, Serializer};
 use tracing::info;
+use winnow::Parser;
+
+use crate::day1::parsing::parse_rotations;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Lock {
-    current_pos: i64,
+    dial: Dial,
     zero_count: usize,
     total_zero_hits: usize,
 }
 
-impl Default for Lock {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+/// A dial with `size` positions, numbered from 0 and wrapping around in both directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dial {
+    size: usize,
+    position: usize,
+}
+
+impl Default for Dial {
     fn default() -> Self {
+        Self::new(Self::SIZE, Self::START)
+    }
+}
+
+impl Dial {
+    pub const SIZE: usize = 100;
+    pub const START: usize = 50;
+
+    /// Panics if the dial has no positions or `start` is not one of them.
+    pub fn new(size: usize, start: usize) -> Self {
+        assert!(
+            start < size,
+            "start {start} is not on a dial of size {size}"
+        );
         Self {
-            current_pos: 50,
-            zero_count: 0,
-            total_zero_hits: 0,
+            size,
+            position: start,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Turns the dial `distance` clicks and returns how many of them ended on 0, the final click
+    /// included.
+    pub fn rotate(&mut self, direction: Direction, distance: usize) -> usize {
+        let first_zero = match direction {
+            Direction::Right => self.size - self.position,
+            Direction::Left if self.position == 0 => self.size,
+            Direction::Left => self.position,
+        };
+        let hits = self.clicks_from(first_zero, distance);
+
+        let distance = distance % self.size;
+        self.position = match direction {
+            Direction::Right => (self.position + distance) % self.size,
+            Direction::Left => (self.position + self.size - distance) % self.size,
+        };
+
+        hits
+    }
+
+    /// How many clicks of a rotation would wrap the dial around, from the last position to 0
+    /// turning right or from 0 to the last position turning left.
+    pub fn wraps(&self, direction: Direction, distance: usize) -> usize {
+        let first_wrap = match direction {
+            Direction::Right => self.size - self.position,
+            Direction::Left => self.position + 1,
+        };
+        self.clicks_from(first_wrap, distance)
+    }
+
+    // every `size` clicks from click `first` on, counting up to and including click `distance`
+    fn clicks_from(&self, first: usize, distance: usize) -> usize {
+        match distance.checked_sub(first) {
+            Some(remaining) => remaining / self.size + 1,
+            None => 0,
         }
     }
 }
 
-pub fn puzzle(input: &str) -> (usize, usize) {
-    let count = input
-        .trim()
-        .lines()
-        .fold(Lock::default(), |mut lock_state, line| {
-            if !line.is_empty() {
-                let distance: usize = line[1..].parse().unwrap();
-                let new_pos: i64 = if line.starts_with("R") {
-                    lock_state.current_pos + (distance as i64 % 100)
-                } else {
-                    lock_state.current_pos - (distance as i64 % 100)
-                };
+/// Dials linked like an odometer: every time a dial wraps around it turns the next one by a click.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkedDials {
+    dials: Vec<Dial>,
+}
 
-                if new_pos > 100 || (new_pos < 0 && lock_state.current_pos != 0) {
-                    lock_state.total_zero_hits += 1;
-                }
+impl LinkedDials {
+    /// The first dial is the one being turned, the last one turns the slowest.
+    pub fn new(dials: Vec<Dial>) -> Self {
+        Self { dials }
+    }
 
-                lock_state.total_zero_hits += distance / 100;
+    pub fn dials(&self) -> &[Dial] {
+        &self.dials
+    }
 
-                lock_state.current_pos = new_pos.rem_euclid(100);
-                if lock_state.current_pos == 0 {
-                    lock_state.zero_count += 1;
-                    lock_state.total_zero_hits += 1;
-                }
+    pub fn positions(&self) -> Vec<usize> {
+        self.dials.iter().map(Dial::position).collect()
+    }
 
-                info!("line: {line:>3}: {lock_state:?}");
-            }
-            lock_state
-        });
-    (count.zero_count, count.total_zero_hits)
+    /// Turns the first dial `distance` clicks and returns the zero crossings of every dial.
+    pub fn rotate(&mut self, direction: Direction, distance: usize) -> Vec<usize> {
+        let mut clicks = distance;
+        self.dials
+            .iter_mut()
+            .map(|dial| {
+                let wraps = dial.wraps(direction, clicks);
+                let hits = dial.rotate(direction, clicks);
+                clicks = wraps;
+                hits
+            })
+            .collect()
+    }
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Parsed<'a> = Vec<Rotation>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_rotations
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(rotations: &Self::Parsed<'_>) -> Answer {
+        turn(rotations).zero_count.into()
+    }
+
+    fn part2(rotations: &Self::Parsed<'_>) -> Answer {
+        turn(rotations).total_zero_hits.into()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Left(usize),
+    Right(usize),
+}
+
+impl Rotation {
+    pub fn direction(&self) -> Direction {
+        match self {
+            Rotation::Left(_) => Direction::Left,
+            Rotation::Right(_) => Direction::Right,
+        }
+    }
+
+    pub fn distance(&self) -> usize {
+        match *self {
+            Rotation::Left(distance) | Rotation::Right(distance) => distance,
+        }
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rotation::Left(distance) => write!(f, "L{distance}"),
+            Rotation::Right(distance) => write!(f, "R{distance}"),
+        }
+    }
+}
+
+// written as in the input, e.g. `L68`, so traces stay flat enough for CSV
+impl Serialize for Rotation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// What a single rotation did to the dial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DialStep {
+    pub instruction: Rotation,
+    pub from: usize,
+    pub to: usize,
+    /// Clicks that ended on 0, the landing included
+    pub crossed_zero: usize,
+    pub landed_on_zero: bool,
+}
+
+/// Every step of turning `dial` through `rotations`.
+pub fn trace(mut dial: Dial, rotations: &[Rotation]) -> impl Iterator<Item = DialStep> + '_ {
+    rotations.iter().map(move |&instruction| {
+        let from = dial.position();
+        let crossed_zero = dial.rotate(instruction.direction(), instruction.distance());
+        DialStep {
+            instruction,
+            from,
+            to: dial.position(),
+            crossed_zero,
+            landed_on_zero: dial.position() == 0,
+        }
+    })
+}
+
+/// Writes a trace as CSV with a header row.
+pub fn write_csv(steps: impl IntoIterator<Item = DialStep>, out: impl Write) -> csv::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    for step in steps {
+        writer.serialize(step)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes a trace as a JSON array of steps.
+pub fn write_json(
+    steps: impl IntoIterator<Item = DialStep>,
+    out: impl Write,
+) -> serde_json::Result<()> {
+    let steps: Vec<_> = steps.into_iter().collect();
+    serde_json::to_writer_pretty(out, &steps)
+}
+
+pub fn puzzle(input: &str) -> Result<(usize, usize), ParseError> {
+    let count = turn(&Day1::parse(input)?);
+    Ok((count.zero_count, count.total_zero_hits))
+}
+
+fn turn(rotations: &[Rotation]) -> Lock {
+    trace(Dial::default(), rotations).fold(Lock::default(), |mut lock_state, step| {
+        lock_state.dial.position = step.to;
+        lock_state.total_zero_hits += step.crossed_zero;
+        if step.landed_on_zero {
+            lock_state.zero_count += 1;
+        }
+
+        info!("rotation: {}: {lock_state:?}", step.instruction);
+        lock_state
+    })
+}
+
+mod parsing {
+    use winnow::{
+        ascii::{digit1, multispace0},
+        combinator::{alt, cut_err, eof, preceded, repeat_till, terminated},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+    };
+
+    use super::Rotation;
+
+    fn parse_distance(input: &mut &str) -> winnow::ModalResult<usize> {
+        digit1
+            .try_map(str::parse)
+            .context(StrContext::Label("distance"))
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
+    }
+
+    fn parse_direction(input: &mut &str) -> winnow::ModalResult<char> {
+        alt(('L', 'R'))
+            .context(StrContext::Label("rotation"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('L')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('R')))
+            .parse_next(input)
+    }
+
+    fn parse_rotation(input: &mut &str) -> winnow::ModalResult<Rotation> {
+        (parse_direction, cut_err(parse_distance))
+            .map(|(direction, distance)| match direction {
+                'L' => Rotation::Left(distance),
+                _ => Rotation::Right(distance),
+            })
+            .parse_next(input)
+    }
+
+    pub fn parse_rotations(input: &mut &str) -> winnow::ModalResult<Vec<Rotation>> {
+        preceded(
+            multispace0,
+            repeat_till(0.., terminated(cut_err(parse_rotation), multispace0), eof),
+        )
+        .map(|(rotations, _)| rotations)
+        .parse_next(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+    use rstest::rstest;
     use test_log::test;
 
     use super::*;
 
+    // turns the dial one click at a time, as the reference for the arithmetic in `Dial::rotate`
+    fn click_by_click(dial: &mut Dial, direction: Direction, distance: usize) -> usize {
+        let mut hits = 0;
+        for _ in 0..distance {
+            dial.position = match direction {
+                Direction::Right => (dial.position + 1) % dial.size,
+                Direction::Left => (dial.position + dial.size - 1) % dial.size,
+            };
+            if dial.position == 0 {
+                hits += 1;
+            }
+        }
+        hits
+    }
+
+    fn simulate_clicks(rotations: &[Rotation]) -> (usize, usize) {
+        let mut dial = Dial::default();
+        let (mut zero_count, mut total_zero_hits) = (0, 0);
+        for rotation in rotations {
+            total_zero_hits += click_by_click(&mut dial, rotation.direction(), rotation.distance());
+            if dial.position() == 0 {
+                zero_count += 1;
+            }
+        }
+        (zero_count, total_zero_hits)
+    }
+
+    fn distance() -> impl Strategy<Value = usize> {
+        prop_oneof![
+            Just(0),
+            (1..10usize).prop_map(|turns| turns * Dial::SIZE),
+            1..Dial::SIZE,
+            0..1000usize,
+        ]
+    }
+
+    fn rotation() -> impl Strategy<Value = Rotation> {
+        (any::<bool>(), distance()).prop_map(|(left, distance)| {
+            if left {
+                Rotation::Left(distance)
+            } else {
+                Rotation::Right(distance)
+            }
+        })
+    }
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop_oneof![Just(Direction::Left), Just(Direction::Right)]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_turn_matches_clicks(rotations in prop::collection::vec(rotation(), 0..200)) {
+            let lock = turn(&rotations);
+            prop_assert_eq!(
+                (lock.zero_count, lock.total_zero_hits),
+                simulate_clicks(&rotations)
+            );
+        }
+
+        // lands on 0 first, so the following rotation starts from 0
+        #[test]
+        fn prop_turn_from_zero(first in rotation(), turns in 0..5usize, rest in prop::collection::vec(rotation(), 0..20)) {
+            let to_zero = match first.direction() {
+                Direction::Right => Rotation::Right(Dial::SIZE - Dial::START + turns * Dial::SIZE),
+                Direction::Left => Rotation::Left(Dial::START + turns * Dial::SIZE),
+            };
+            let rotations: Vec<_> = [to_zero, first].into_iter().chain(rest).collect();
+
+            let lock = turn(&rotations);
+            prop_assert_eq!(
+                (lock.zero_count, lock.total_zero_hits),
+                simulate_clicks(&rotations)
+            );
+        }
+
+        #[test]
+        fn prop_rotate_matches_clicks(
+            (size, start) in (1..50usize).prop_flat_map(|size| (Just(size), 0..size)),
+            direction in direction(),
+            distance in 0..500usize,
+        ) {
+            let mut dial = Dial::new(size, start);
+            let mut reference = dial;
+
+            prop_assert_eq!(
+                dial.rotate(direction, distance),
+                click_by_click(&mut reference, direction, distance)
+            );
+            prop_assert_eq!(dial, reference);
+        }
+    }
+
     #[test]
     fn test_empty_input() {
-        let result = puzzle("");
+        let result = puzzle("").unwrap();
         assert_eq!(result.0, 0);
     }
 
@@ -76,13 +419,131 @@ L99
 R14
 L82
 ",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (3, 6));
     }
 
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day1_input.txt"));
-        assert_eq!(result, (1150, 6738));
+    #[rstest]
+    #[case(50, Direction::Right, 50, 1, 0)]
+    #[case(50, Direction::Right, 49, 0, 99)]
+    #[case(50, Direction::Right, 1000, 10, 50)]
+    #[case(50, Direction::Left, 68, 1, 82)]
+    #[case(0, Direction::Left, 5, 0, 95)]
+    #[case(0, Direction::Left, 100, 1, 0)]
+    #[case(0, Direction::Right, 0, 0, 0)]
+    #[case(1, Direction::Left, 201, 3, 0)]
+    fn test_rotate(
+        #[case] start: usize,
+        #[case] direction: Direction,
+        #[case] distance: usize,
+        #[case] expected_hits: usize,
+        #[case] expected_position: usize,
+    ) {
+        let mut dial = Dial::new(100, start);
+        assert_eq!(dial.rotate(direction, distance), expected_hits);
+        assert_eq!(dial.position(), expected_position);
+    }
+
+    #[test]
+    #[rstest]
+    #[case(0, Direction::Left, 1, 1)]
+    #[case(0, Direction::Right, 9, 0)]
+    #[case(9, Direction::Right, 1, 1)]
+    #[case(9, Direction::Left, 10, 1)]
+    #[case(9, Direction::Left, 9, 0)]
+    fn test_wraps(
+        #[case] start: usize,
+        #[case] direction: Direction,
+        #[case] distance: usize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(Dial::new(10, start).wraps(direction, distance), expected);
+    }
+
+    #[test]
+    fn test_linked_dials() {
+        // the first dial holds the lowest digit
+        let mut odometer = LinkedDials::new(vec![Dial::new(10, 0); 3]);
+
+        assert_eq!(odometer.rotate(Direction::Right, 123), [12, 1, 0]);
+        assert_eq!(odometer.positions(), [3, 2, 1]);
+
+        // 123 back to 099, passing 120, 110 and 100 on the way
+        assert_eq!(odometer.rotate(Direction::Left, 24), [3, 1, 1]);
+        assert_eq!(odometer.positions(), [9, 9, 0]);
+
+        assert_eq!(odometer.rotate(Direction::Right, 1), [1, 1, 0]);
+        assert_eq!(odometer.positions(), [0, 0, 1]);
+    }
+
+    #[test]
+    fn test_configured_dial() {
+        let mut dial = Dial::new(5, 3);
+        assert_eq!(dial.rotate(Direction::Right, 2), 1);
+        assert_eq!(dial.rotate(Direction::Left, 11), 2);
+        assert_eq!(dial.position(), 4);
+    }
+
+    #[test]
+    fn test_trace() {
+        let rotations = Day1::parse("L68\nL30\nR48\nR200").unwrap();
+        let steps: Vec<_> = trace(Dial::default(), &rotations).collect();
+
+        assert_eq!(
+            steps[0],
+            DialStep {
+                instruction: Rotation::Left(68),
+                from: 50,
+                to: 82,
+                crossed_zero: 1,
+                landed_on_zero: false,
+            }
+        );
+        assert_eq!((steps[2].from, steps[2].to), (52, 0));
+        assert!(steps[2].landed_on_zero);
+        assert_eq!(steps[3].crossed_zero, 2);
+    }
+
+    #[test]
+    fn test_trace_export() {
+        let rotations = Day1::parse("L68\nR18").unwrap();
+
+        let mut csv = Vec::new();
+        write_csv(trace(Dial::default(), &rotations), &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "\
+instruction,from,to,crossed_zero,landed_on_zero
+L68,50,82,1,false
+R18,82,0,1,true
+"
+        );
+
+        let mut json = Vec::new();
+        write_json(trace(Dial::default(), &rotations), &mut json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(
+            json[1],
+            serde_json::json!({
+                "instruction": "R18",
+                "from": 82,
+                "to": 0,
+                "crossed_zero": 1,
+                "landed_on_zero": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = puzzle("L68\nL30\nX48\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 1));
+        assert_eq!(error.expected(), ["`L`", "`R`"]);
+
+        let error = puzzle("L68\nR\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 2));
+        assert_eq!(error.expected(), ["digit"]);
     }
 }
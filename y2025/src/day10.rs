@@ -1,34 +1,66 @@
+use std::collections::HashMap;
+
 use crate::day10::parsing::parse_full_input;
+use aoc_core::{Answer, ParseError, Solution};
 use pathfinding::prelude::*;
 use rayon::prelude::*;
-use tracing::info;
 use winnow::Parser;
+#[cfg(feature = "z3")]
 use z3::{Optimize, ast::Int};
 
 #[derive(Debug, PartialEq)]
-struct Machine {
+pub struct Machine {
     desired_state: u64,
     button_masks: Vec<u64>,
     joltage_requirements: Vec<u64>,
 }
 
-pub fn part1(input: &str) -> u64 {
-    let (left_over, input) = parse_full_input.parse_peek(input).unwrap();
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Parsed<'a> = Vec<Machine>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_full_input
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
 
-    info!(?left_over);
+    fn part1(machines: &Self::Parsed<'_>) -> Answer {
+        machines
+            .par_iter()
+            .map(part1_solve_single_machine)
+            .sum::<Option<u64>>()
+            .map_or(Answer::Unsolved, Answer::from)
+    }
+
+    fn part2(machines: &Self::Parsed<'_>) -> Answer {
+        machines
+            .par_iter()
+            .map(part2_solve_single_machine)
+            .sum::<Option<u64>>()
+            .map_or(Answer::Unsolved, Answer::from)
+    }
+}
 
-    input.par_iter().map(part1_solve_single_machine).sum()
+/// The fewest presses to light every machine up, `None` if any machine can't be.
+pub fn part1(input: &str) -> Result<Option<u64>, ParseError> {
+    Ok(Day10::parse(input)?
+        .par_iter()
+        .map(part1_solve_single_machine)
+        .sum())
 }
 
-fn part1_solve_single_machine(machine: &Machine) -> u64 {
-    let res = dijkstra(
+fn part1_solve_single_machine(machine: &Machine) -> Option<u64> {
+    let (_, presses) = dijkstra(
         &0u64,
         |state| press_buttons_part1(*state, &machine.button_masks),
         |state| state == &machine.desired_state,
-    )
-    .unwrap();
+    )?;
 
-    res.1 as u64
+    Some(presses.into())
 }
 
 fn press_buttons_part1(state: u64, buttons: &[u64]) -> impl Iterator<Item = (u64, u32)> {
@@ -41,15 +73,26 @@ fn push_button_part1(state: u64, button_mask: u64) -> u64 {
     state ^ button_mask
 }
 
-pub fn part2(input: &str) -> u64 {
-    let (left_over, input) = parse_full_input.parse_peek(input).unwrap();
+/// The fewest presses to reach the joltage of every machine, `None` if any machine can't.
+pub fn part2(input: &str) -> Result<Option<u64>, ParseError> {
+    Ok(Day10::parse(input)?
+        .par_iter()
+        .map(part2_solve_single_machine)
+        .sum())
+}
 
-    info!(?left_over);
+#[cfg(feature = "z3")]
+fn part2_solve_single_machine(machine: &Machine) -> Option<u64> {
+    part2_z3_solver(machine)
+}
 
-    input.par_iter().map(part2_solve_single_machine).sum()
+#[cfg(not(feature = "z3"))]
+fn part2_solve_single_machine(machine: &Machine) -> Option<u64> {
+    part2_halving_solver(machine)
 }
 
-fn part2_solve_single_machine(machine: &Machine) -> u64 {
+#[cfg(feature = "z3")]
+fn part2_z3_solver(machine: &Machine) -> Option<u64> {
     let optimizer = Optimize::new();
 
     let (total_presses, state) = machine.button_masks.iter().enumerate().fold(
@@ -88,13 +131,94 @@ fn part2_solve_single_machine(machine: &Machine) -> u64 {
 
     match optimizer.check(&[]) {
         z3::SatResult::Sat => {
-            let model = optimizer.get_model().unwrap();
-            model.eval(&total_presses, true).unwrap().as_u64().unwrap()
+            let model = optimizer.get_model()?;
+            model.eval(&total_presses, true)?.as_u64()
         }
-        _ => u64::MAX,
+        _ => None,
     }
 }
 
+// every press count is its lowest bit plus an even rest, so the buttons pressed an odd number of
+// times have to produce the parity of each joltage and the remaining presses come in pairs,
+// i.e. solve the halved remainder and press everything twice
+#[cfg_attr(feature = "z3", allow(dead_code))]
+fn part2_halving_solver(machine: &Machine) -> Option<u64> {
+    let combinations = button_combinations(machine);
+
+    fewest_presses(
+        &machine.joltage_requirements,
+        &combinations,
+        &mut HashMap::new(),
+    )
+}
+
+/// The joltage added by pressing each subset of buttons once, grouped by the joltage parities.
+type Combinations = HashMap<u64, Vec<(u64, Vec<u64>)>>;
+
+fn button_combinations(machine: &Machine) -> Combinations {
+    let mut combinations = Combinations::new();
+
+    for subset in 0..1u64 << machine.button_masks.len() {
+        let mut joltage = vec![0; machine.joltage_requirements.len()];
+        for button in BitIter(subset) {
+            for idx in BitIter(machine.button_masks[button]) {
+                joltage[idx] += 1;
+            }
+        }
+
+        combinations
+            .entry(parity(&joltage))
+            .or_default()
+            .push((subset.count_ones() as u64, joltage));
+    }
+
+    combinations
+}
+
+fn fewest_presses(
+    joltage: &[u64],
+    combinations: &Combinations,
+    cache: &mut HashMap<Vec<u64>, Option<u64>>,
+) -> Option<u64> {
+    if joltage.iter().all(|level| *level == 0) {
+        return Some(0);
+    }
+    if let Some(presses) = cache.get(joltage) {
+        return *presses;
+    }
+
+    let fewest = combinations
+        .get(&parity(joltage))
+        .into_iter()
+        .flatten()
+        .filter(|(_, added)| {
+            added
+                .iter()
+                .zip(joltage)
+                .all(|(added, level)| added <= level)
+        })
+        .filter_map(|(presses, added)| {
+            let halved: Vec<_> = joltage
+                .iter()
+                .zip(added)
+                .map(|(level, added)| (level - added) / 2)
+                .collect();
+
+            Some(presses + 2 * fewest_presses(&halved, combinations, cache)?)
+        })
+        .min();
+
+    cache.insert(joltage.to_vec(), fewest);
+    fewest
+}
+
+fn parity(joltage: &[u64]) -> u64 {
+    joltage
+        .iter()
+        .rev()
+        .fold(0, |acc, level| (acc << 1) | (level & 1))
+}
+
 struct BitIter(u64);
 
 impl Iterator for BitIter {
@@ -114,15 +238,24 @@ mod parsing {
     use super::*;
 
     use winnow::{
-        ascii::{digit1, line_ending, space0, space1},
-        combinator::{alt, delimited, repeat, separated, seq},
+        ascii::{digit1, multispace0, space0, space1},
+        combinator::{
+            alt, cut_err, delimited, eof, fail, preceded, repeat, repeat_till, separated,
+            terminated,
+        },
+        error::{StrContext, StrContextValue},
+        stream::Stream,
     };
 
-    fn parse_single_light(input: &mut &str) -> winnow::Result<bool> {
-        alt((".", "#")).map(|char| char == "#").parse_next(input)
+    fn parse_single_light(input: &mut &str) -> winnow::ModalResult<bool> {
+        alt((".", "#"))
+            .map(|char| char == "#")
+            .context(StrContext::Expected(StrContextValue::CharLiteral('.')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('#')))
+            .parse_next(input)
     }
 
-    fn parse_multiple_lights(input: &mut &str) -> winnow::Result<u64> {
+    fn parse_multiple_lights(input: &mut &str) -> winnow::ModalResult<u64> {
         repeat(0.., parse_single_light)
             .map(|lights: Vec<bool>| {
                 lights
@@ -133,48 +266,97 @@ mod parsing {
             .parse_next(input)
     }
 
-    fn parse_desired_state(input: &mut &str) -> winnow::Result<u64> {
-        delimited("[", parse_multiple_lights, "]").parse_next(input)
+    fn parse_desired_state(input: &mut &str) -> winnow::ModalResult<u64> {
+        delimited("[", parse_multiple_lights, "]")
+            .context(StrContext::Label("indicator lights"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('[')))
+            .parse_next(input)
     }
 
-    fn parse_cs_digits(input: &mut &str) -> winnow::Result<Vec<u64>> {
+    fn parse_cs_digits(input: &mut &str) -> winnow::ModalResult<Vec<u64>> {
         separated(0.., digit1.try_map(str::parse::<u64>), ",").parse_next(input)
     }
 
-    fn parse_single_button(input: &mut &str) -> winnow::Result<u64> {
+    fn parse_light_indices(input: &mut &str) -> winnow::ModalResult<Vec<u64>> {
+        // each button is stored as a bit mask over the lights
+        separated(
+            0..,
+            digit1
+                .try_map(str::parse::<u64>)
+                .verify(|idx| *idx < u64::BITS as u64)
+                .context(StrContext::Label("light index"))
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "index below 64",
+                ))),
+            ",",
+        )
+        .parse_next(input)
+    }
+
+    fn parse_single_button(input: &mut &str) -> winnow::ModalResult<u64> {
         delimited(
             "(",
-            parse_cs_digits.map(|numbers: Vec<u64>| {
+            parse_light_indices.map(|numbers: Vec<u64>| {
                 numbers.iter().fold(0u64, |acc, digit| acc | (1 << digit))
             }),
             ")",
         )
+        .context(StrContext::Label("button"))
         .parse_next(input)
     }
 
-    fn parse_button_masks(input: &mut &str) -> winnow::Result<Vec<u64>> {
+    fn parse_button_masks(input: &mut &str) -> winnow::ModalResult<Vec<u64>> {
         separated(0.., parse_single_button, space1).parse_next(input)
     }
 
-    fn parse_joltage_requirements(input: &mut &str) -> winnow::Result<Vec<u64>> {
-        delimited("{", parse_cs_digits, "}").parse_next(input)
+    fn parse_joltage_requirements(input: &mut &str) -> winnow::ModalResult<Vec<u64>> {
+        delimited("{", parse_cs_digits, "}")
+            .context(StrContext::Label("joltage requirements"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('(')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('{')))
+            .parse_next(input)
     }
 
-    pub fn parse_line(input: &mut &str) -> winnow::Result<Machine> {
-        seq!(
-            Machine {
-                desired_state: parse_desired_state,
-                _: space0,
-                button_masks: parse_button_masks,
-                _: space0,
-                joltage_requirements: parse_joltage_requirements
-            }
-        )
-        .parse_next(input)
+    pub fn parse_line(input: &mut &str) -> winnow::ModalResult<Machine> {
+        let (desired_state, _, button_masks, _) =
+            (parse_desired_state, space0, parse_button_masks, space0).parse_next(input)?;
+        let start = input.checkpoint();
+        let joltage_requirements = parse_joltage_requirements.parse_next(input)?;
+
+        // every joltage is a bit of the button masks and of the joltage parities
+        let joltages = joltage_requirements.len();
+        let expected = if joltages > u64::BITS as usize {
+            Some("at most 64 requirements")
+        } else if button_masks
+            .iter()
+            .any(|mask| joltages < u64::BITS as usize && mask >> joltages != 0)
+        {
+            Some("a requirement for every index of a button")
+        } else {
+            None
+        };
+        if let Some(expected) = expected {
+            input.reset(&start);
+            return cut_err(fail)
+                .context(StrContext::Label("joltage requirements"))
+                .context(StrContext::Expected(StrContextValue::Description(expected)))
+                .parse_next(input);
+        }
+
+        Ok(Machine {
+            desired_state,
+            button_masks,
+            joltage_requirements,
+        })
     }
 
-    pub fn parse_full_input(input: &mut &str) -> winnow::Result<Vec<Machine>> {
-        separated(0.., parse_line, line_ending).parse_next(input)
+    pub fn parse_full_input(input: &mut &str) -> winnow::ModalResult<Vec<Machine>> {
+        preceded(
+            multispace0,
+            repeat_till(0.., terminated(cut_err(parse_line), multispace0), eof),
+        )
+        .map(|(machines, _)| machines)
+        .parse_next(input)
     }
 
     #[cfg(test)]
@@ -239,24 +421,17 @@ mod tests {
 [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
 [.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}";
 
-    const INPUT: &str = include_str!("day10_input.txt");
-
     #[test]
     fn test_part1_example_input() {
-        let result = part1(EXAMPLE);
-        assert_eq!(result, 7);
-    }
-
-    #[test]
-    fn test_part1_input() {
-        let result = part1(INPUT);
-        assert_eq!(result, 486);
+        let result = part1(EXAMPLE).unwrap();
+        assert_eq!(result, Some(7));
     }
 
     #[test]
     #[rstest]
-    #[case("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}", 2)]
-    fn test_part1_solve_single_machine(#[case] input: &str, #[case] expected: u64) {
+    #[case("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}", Some(2))]
+    #[case("[.#] (0) {1,1}", None)]
+    fn test_part1_solve_single_machine(#[case] input: &str, #[case] expected: Option<u64>) {
         use crate::day10::parsing::parse_line;
 
         let (_, machine) = parse_line.parse_peek(input).unwrap();
@@ -268,20 +443,49 @@ mod tests {
 
     #[test]
     fn test_part2_example_input() {
-        let result = part2(EXAMPLE);
-        assert_eq!(result, 33);
+        let result = part2(EXAMPLE).unwrap();
+        assert_eq!(result, Some(33));
+    }
+
+    #[test]
+    fn test_unsolvable_machine() {
+        // the second light can't be reached, by a single unsolvable machine among solvable ones
+        let input = format!("{EXAMPLE}\n[.#] (0) {{1,1}}");
+        assert_eq!(part1(&input).unwrap(), None);
+        assert_eq!(part2(&input).unwrap(), None);
+        assert_eq!(
+            Day10::solve(&input).unwrap(),
+            (Answer::Unsolved, Answer::Unsolved)
+        );
     }
 
     #[test]
-    fn test_part2_input() {
-        let result = part2(INPUT);
-        assert_eq!(result, 17820);
+    fn test_invalid_input() {
+        let error =
+            part1("[.##.] (3) (1,3) {3,5,4,7}\n[...#.] (0,2,3,4) (2,3 {7,5,12,7,2}").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 19));
+        assert_eq!(error.expected(), ["`(`", "`{`"]);
+    }
+
+    #[test]
+    #[rstest]
+    #[case("[.#] (5) {1,1}", "a requirement for every index of a button")]
+    #[case("[.#] (0) (1,2) {1,1}", "a requirement for every index of a button")]
+    #[case(
+        &format!("[.] (0) {{{}}}", ["1"; 65].join(",")),
+        "at most 64 requirements"
+    )]
+    fn test_joltage_count(#[case] input: &str, #[case] expected: &str) {
+        let error = Day10::parse(&format!("{EXAMPLE}\n{input}")).unwrap_err();
+        assert_eq!(error.line(), 4);
+        assert_eq!(error.column(), input.find('{').unwrap() + 1);
+        assert_eq!(error.expected(), [expected]);
     }
 
     #[test]
     #[rstest]
-    #[case("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}", 10)]
-    fn test_part2_solve_single_machine(#[case] input: &str, #[case] expected: u64) {
+    #[case("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}", Some(10))]
+    fn test_part2_solve_single_machine(#[case] input: &str, #[case] expected: Option<u64>) {
         use crate::day10::parsing::parse_line;
 
         let (_, machine) = parse_line.parse_peek(input).unwrap();
@@ -290,4 +494,24 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    #[rstest]
+    #[case("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}", Some(10))]
+    #[case(
+        "[...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}",
+        Some(12)
+    )]
+    #[case(
+        "[.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}",
+        Some(11)
+    )]
+    #[case("[.#] (0) {1,1}", None)]
+    fn test_part2_halving_solver(#[case] input: &str, #[case] expected: Option<u64>) {
+        use crate::day10::parsing::parse_line;
+
+        let (_, machine) = parse_line.parse_peek(input).unwrap();
+
+        assert_eq!(part2_halving_solver(&machine), expected);
+    }
 }
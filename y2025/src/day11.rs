@@ -1,57 +1,94 @@
 use std::collections::BTreeMap;
 
+use aoc_core::{Answer, ParseError, Solution};
 use pathfinding::prelude::*;
 use tracing::info;
 use winnow::{
     Parser,
-    ascii::{alpha1, multispace1, space1},
-    combinator::{separated, seq},
+    ascii::{alpha1, multispace0, space1},
+    combinator::{cut_err, eof, preceded, repeat_till, separated, seq, terminated},
+    error::{StrContext, StrContextValue},
 };
 
-pub fn part1(input: &str) -> usize {
-    let (_, input) = parse_input.parse_peek(input).unwrap();
+pub struct Day11;
 
-    info!(?input);
+type Devices<'a> = BTreeMap<&'a str, Vec<&'a str>>;
 
-    count_from_to("you", "out", &input)
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Parsed<'a> = Devices<'a>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        let devices = parse_input
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))?;
+
+        info!(?devices);
+
+        Ok(devices)
+    }
+
+    fn part1(devices: &Self::Parsed<'_>) -> Answer {
+        count_you_out(devices).into()
+    }
+
+    fn part2(devices: &Self::Parsed<'_>) -> Answer {
+        count_svr_out_via_dac_fft(devices).into()
+    }
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(count_you_out(&Day11::parse(input)?))
 }
 
-fn parse_input<'a>(input: &mut &'a str) -> winnow::Result<BTreeMap<&'a str, Vec<&'a str>>> {
-    separated(
-        0..,
-        seq!(
-            alpha1,
-            _: ": ",
-            separated(0.., alpha1, space1)
-        ),
-        multispace1,
+fn count_you_out(input: &Devices) -> usize {
+    count_from_to("you", "out", input)
+}
+
+fn parse_device<'a>(input: &mut &'a str) -> winnow::ModalResult<(&'a str, Vec<&'a str>)> {
+    seq!(
+        alpha1.context(StrContext::Expected(StrContextValue::Description("device name"))),
+        _: ": ".context(StrContext::Expected(StrContextValue::StringLiteral(": "))),
+        separated(0.., alpha1, space1)
+    )
+    .context(StrContext::Label("device"))
+    .parse_next(input)
+}
+
+fn parse_input<'a>(input: &mut &'a str) -> winnow::ModalResult<Devices<'a>> {
+    preceded(
+        multispace0,
+        repeat_till(0.., terminated(cut_err(parse_device), multispace0), eof),
     )
+    .map(|(devices, _)| devices)
     .parse_next(input)
 }
 
-pub fn part2(input: &str) -> usize {
-    let (_, input) = parse_input.parse_peek(input).unwrap();
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    Ok(count_svr_out_via_dac_fft(&Day11::parse(input)?))
+}
 
-    info!(?input);
-    let count_dac_fft = count_from_to("dac", "fft", &input);
+fn count_svr_out_via_dac_fft(input: &Devices) -> usize {
+    let count_dac_fft = count_from_to("dac", "fft", input);
 
     // the graph cannot loop, or else the answer would be infinite
     // so we just need to know which comes first, dac or fft
     if count_dac_fft == 0 {
         // dac -> fft has 0 paths therefore fft must come first (or there are 0 paths)
-        let count_svr_fft = count_from_to("svr", "fft", &input);
-        let count_fft_dac = count_from_to("fft", "dac", &input);
-        let count_dac_out = count_from_to("dac", "out", &input);
+        let count_svr_fft = count_from_to("svr", "fft", input);
+        let count_fft_dac = count_from_to("fft", "dac", input);
+        let count_dac_out = count_from_to("dac", "out", input);
         count_svr_fft * count_fft_dac * count_dac_out
     } else {
         // dac comes first and we complete the calculations
-        let count_svr_dac = count_from_to("svr", "dac", &input);
-        let count_fft_out = count_from_to("fft", "out", &input);
+        let count_svr_dac = count_from_to("svr", "dac", input);
+        let count_fft_out = count_from_to("fft", "out", input);
         count_svr_dac * count_dac_fft * count_fft_out
     }
 }
 
-fn count_from_to(start: &str, end: &str, input: &BTreeMap<&str, Vec<&str>>) -> usize {
+fn count_from_to(start: &str, end: &str, input: &Devices) -> usize {
     let empty_vec = vec![];
     count_paths(
         &start,
@@ -78,20 +115,13 @@ ggg: out
 hhh: ccc fff iii
 iii: out
 ";
-    const INPUT: &str = include_str!("day11_input.txt");
 
     #[test]
     fn test_part1_example_input() {
-        let result = part1(EXAMPLE);
+        let result = part1(EXAMPLE).unwrap();
         assert_eq!(result, 5);
     }
 
-    #[test]
-    fn test_part1_input() {
-        let result = part1(INPUT);
-        assert_eq!(result, 574);
-    }
-
     #[test]
     fn test_part2_example_input() {
         let result = part2(
@@ -109,13 +139,15 @@ dac: fff
 fff: ggg hhh
 ggg: out
 hhh: out",
-        );
+        )
+        .unwrap();
         assert_eq!(result, 2);
     }
 
     #[test]
-    fn test_part2_input() {
-        let result = part2(INPUT);
-        assert_eq!(result, 306594217920240);
+    fn test_invalid_input() {
+        let error = part1("aaa: you hhh\nyou bbb ccc").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 4));
+        assert_eq!(error.expected(), ["`: `"]);
     }
 }
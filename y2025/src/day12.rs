@@ -1,7 +1,12 @@
+use std::collections::{BTreeSet, HashSet};
+
+use aoc_core::{Answer, Grid, ParseError, Solution};
 use glam::U64Vec2;
+#[cfg(feature = "z3")]
 use ndarray::Array2;
 use tracing::debug;
 use winnow::Parser;
+#[cfg(feature = "z3")]
 use z3::{
     Solver,
     ast::{Bool, Int},
@@ -9,9 +14,34 @@ use z3::{
 
 use crate::day12::parsing::parse_input;
 
-pub fn puzzle(input: &str) -> usize {
-    let (_, input) = parse_input.parse_peek(input).unwrap();
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Parsed<'a> = Input;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_input
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(input: &Self::Parsed<'_>) -> Answer {
+        count_possible_trees(input).into()
+    }
+
+    // the last day only has a single puzzle
+    fn part2(_: &Self::Parsed<'_>) -> Answer {
+        Answer::Unsolved
+    }
+}
+
+pub fn puzzle(input: &str) -> Result<usize, ParseError> {
+    Ok(count_possible_trees(&Day12::parse(input)?))
+}
 
+fn count_possible_trees(input: &Input) -> usize {
     input
         .trees
         .iter()
@@ -20,14 +50,14 @@ pub fn puzzle(input: &str) -> usize {
 }
 
 #[derive(Debug)]
-struct Input {
+pub struct Input {
     presents: Vec<Shape>,
     trees: Vec<Tree>,
 }
 
 #[derive(Debug)]
-struct Shape {
-    shape: Array2<bool>,
+pub struct Shape {
+    shape: Grid<bool>,
 }
 
 impl Shape {
@@ -37,7 +67,7 @@ impl Shape {
 }
 
 #[derive(Debug)]
-struct Tree {
+pub struct Tree {
     dimensions: U64Vec2,
     present_counts: Vec<u64>,
 }
@@ -69,10 +99,180 @@ impl Tree {
 
         debug!(?self);
 
-        z3_solver(presents, &self.dimensions, &self.present_counts)
+        #[cfg(feature = "z3")]
+        let possible = z3_solver(presents, &self.dimensions, &self.present_counts);
+        #[cfg(not(feature = "z3"))]
+        let possible = packing_solver(presents, &self.dimensions, &self.present_counts);
+
+        possible
+    }
+}
+
+/// Tries to fit the presents, in any rotation or mirrored, by backtracking over the cells.
+#[cfg_attr(feature = "z3", allow(dead_code))]
+fn packing_solver(presents: &[Shape], dims: &U64Vec2, desired_presents: &[u64]) -> bool {
+    let total_present_area: u64 = desired_presents
+        .iter()
+        .zip(presents)
+        .map(|(count, present)| count * present.area())
+        .sum();
+
+    let mut packing = Packing {
+        // presents can be rotated, so scan along the shorter side to keep the frontier small
+        width: dims.x.min(dims.y) as usize,
+        taken: vec![false; (dims.x * dims.y) as usize],
+        orientations: presents.iter().map(Shape::orientations).collect(),
+        // presents without any filled space always fit
+        remaining: desired_presents
+            .iter()
+            .zip(presents)
+            .map(|(count, present)| if present.area() == 0 { 0 } else { *count })
+            .collect(),
+        spare_cells: dims.x * dims.y - total_present_area,
+        dead_ends: HashSet::new(),
+    };
+
+    packing.fill_from(0)
+}
+
+struct Packing {
+    width: usize,
+    taken: Vec<bool>,
+    /// The distinct orientations of each present as (row, column) offsets, sorted row by row
+    orientations: Vec<Vec<Vec<(usize, usize)>>>,
+    remaining: Vec<u64>,
+    /// The number of cells that can still be left empty
+    spare_cells: u64,
+    /// Failed (first free cell, remaining presents, cells taken below it) states
+    dead_ends: HashSet<(usize, Vec<u64>, Vec<bool>)>,
+}
+
+impl Packing {
+    // every cell before `cell` is decided, so the next present has to cover the first free cell
+    // with its own first cell or that cell stays empty
+    fn fill_from(&mut self, cell: usize) -> bool {
+        if self.remaining.iter().all(|count| *count == 0) {
+            return true;
+        }
+        let Some(cell) = (cell..self.taken.len()).find(|cell| !self.taken[*cell]) else {
+            return false;
+        };
+
+        // presents reach at most 3 rows down, nothing beyond that is taken yet
+        let window = cell..(cell + 3 * self.width).min(self.taken.len());
+        let state = (cell, self.remaining.clone(), self.taken[window].to_vec());
+        if self.dead_ends.contains(&state) {
+            return false;
+        }
+
+        let filled = self.fill_at(cell);
+        if !filled {
+            self.dead_ends.insert(state);
+        }
+        filled
+    }
+
+    fn fill_at(&mut self, cell: usize) -> bool {
+        let (row, column) = (cell / self.width, cell % self.width);
+
+        for present in 0..self.remaining.len() {
+            if self.remaining[present] == 0 {
+                continue;
+            }
+
+            for orientation in 0..self.orientations[present].len() {
+                let Some(cells) = self.cells(present, orientation, row, column) else {
+                    continue;
+                };
+
+                self.mark(&cells, true);
+                self.remaining[present] -= 1;
+                let filled = self.fill_from(cell + 1);
+                self.remaining[present] += 1;
+                self.mark(&cells, false);
+
+                if filled {
+                    return true;
+                }
+            }
+        }
+
+        if self.spare_cells > 0 {
+            self.spare_cells -= 1;
+            self.taken[cell] = true;
+            let filled = self.fill_from(cell + 1);
+            self.taken[cell] = false;
+            self.spare_cells += 1;
+
+            return filled;
+        }
+
+        false
+    }
+
+    /// The cells covered by the present when its first cell is put at (`row`, `column`), if free.
+    fn cells(
+        &self,
+        present: usize,
+        orientation: usize,
+        row: usize,
+        column: usize,
+    ) -> Option<Vec<usize>> {
+        let offsets = &self.orientations[present][orientation];
+        let (first_row, first_column) = offsets[0];
+        let height = self.taken.len() / self.width;
+
+        offsets
+            .iter()
+            .map(|(dr, dc)| {
+                let row = row + dr - first_row;
+                let column = (column + dc).checked_sub(first_column)?;
+                (row < height && column < self.width).then_some(row * self.width + column)
+            })
+            .map(|cell| cell.filter(|cell| !self.taken[*cell]))
+            .collect()
+    }
+
+    fn mark(&mut self, cells: &[usize], taken: bool) {
+        for cell in cells {
+            self.taken[*cell] = taken;
+        }
+    }
+}
+
+impl Shape {
+    fn orientations(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut orientations = BTreeSet::new();
+        let mut shape = self.shape.clone();
+
+        for _ in 0..4 {
+            shape = shape.rotated();
+
+            orientations.insert(Self::offsets(&shape));
+            orientations.insert(Self::offsets(&shape.mirrored()));
+        }
+
+        orientations.into_iter().collect()
+    }
+
+    // the filled cells relative to the top-left corner of their bounding box
+    fn offsets(shape: &Grid<bool>) -> Vec<(usize, usize)> {
+        let cells: Vec<_> = shape
+            .indexed_iter()
+            .filter(|(_, filled)| **filled)
+            .map(|(idx, _)| idx)
+            .collect();
+        let min_row = cells.iter().map(|(row, _)| *row).min().unwrap_or(0);
+        let min_column = cells.iter().map(|(_, column)| *column).min().unwrap_or(0);
+
+        cells
+            .into_iter()
+            .map(|(row, column)| (row - min_row, column - min_column))
+            .collect()
     }
 }
 
+#[cfg(feature = "z3")]
 fn z3_solver(presents: &[Shape], dims: &U64Vec2, desired_presents: &[u64]) -> bool {
     let solver = Solver::new();
 
@@ -140,6 +340,7 @@ fn z3_solver(presents: &[Shape], dims: &U64Vec2, desired_presents: &[u64]) -> bo
     matches!(solver.check(), z3::SatResult::Sat)
 }
 
+#[cfg(feature = "z3")]
 fn create_z3_consts(
     solver: &Solver,
     present_idx: usize,
@@ -173,69 +374,90 @@ fn create_z3_consts(
 
 mod parsing {
     use super::*;
+    use aoc_core::grid::char_map;
     use winnow::{
-        ascii::{digit1, multispace1, space1},
-        combinator::{alt, preceded, repeat, separated, seq},
+        ascii::{digit1, multispace0, multispace1, space1},
+        combinator::{alt, cut_err, eof, fail, preceded, repeat_till, separated, seq, terminated},
+        error::{StrContext, StrContextValue},
+        stream::Stream,
     };
 
-    fn parse_taken_spaces(input: &mut &str) -> winnow::Result<Vec<bool>> {
-        repeat(3, alt(("#", ".")).map(|char| char == "#")).parse_next(input)
+    fn parse_taken_space(input: &mut &str) -> winnow::ModalResult<bool> {
+        alt(('#'.value(true), '.'.value(false)))
+            .context(StrContext::Label("shape"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('#')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('.')))
+            .parse_next(input)
     }
 
-    fn parse_shape(input: &mut &str) -> winnow::Result<Shape> {
-        separated(3, parse_taken_spaces, multispace1)
-            .map(|taken_spaces: Vec<_>| {
-                let flat: Vec<_> = taken_spaces.into_iter().flatten().collect();
-                let shape = Array2::from_shape_vec((3, 3), flat).unwrap();
+    pub(super) fn parse_shape(input: &mut &str) -> winnow::ModalResult<Shape> {
+        let start = input.checkpoint();
+        let shape = char_map(parse_taken_space).parse_next(input)?;
+
+        if (shape.rows(), shape.columns()) != (3, 3) {
+            input.reset(&start);
+            return fail
+                .context(StrContext::Label("shape"))
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "3x3 shape",
+                )))
+                .parse_next(input);
+        }
 
-                Shape { shape }
-            })
-            .parse_next(input)
+        Ok(Shape { shape })
     }
 
-    fn parse_present(input: &mut &str) -> winnow::Result<Shape> {
-        preceded(seq!(digit1, ":", multispace1), parse_shape).parse_next(input)
+    fn parse_present(input: &mut &str) -> winnow::ModalResult<Shape> {
+        // a tree also starts with digits, only commit once the `:` shows this is a present
+        preceded(seq!(digit1, ":", multispace1), cut_err(parse_shape)).parse_next(input)
     }
 
-    fn parse_presents(input: &mut &str) -> winnow::Result<Vec<Shape>> {
+    fn parse_presents(input: &mut &str) -> winnow::ModalResult<Vec<Shape>> {
         separated(0.., parse_present, multispace1).parse_next(input)
     }
 
-    fn parse_digits(input: &mut &str) -> winnow::Result<u64> {
-        digit1.try_map(str::parse::<u64>).parse_next(input)
+    fn parse_digits(input: &mut &str) -> winnow::ModalResult<u64> {
+        digit1
+            .try_map(str::parse::<u64>)
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
     }
 
-    fn parse_dimensions(input: &mut &str) -> winnow::Result<U64Vec2> {
+    fn parse_dimensions(input: &mut &str) -> winnow::ModalResult<U64Vec2> {
         seq!(U64Vec2 {
             x: parse_digits,
-            _: "x",
+            _: "x".context(StrContext::Expected(StrContextValue::CharLiteral('x'))),
             y: parse_digits
         })
         .parse_next(input)
     }
 
-    fn parse_present_counts(input: &mut &str) -> winnow::Result<Vec<u64>> {
+    fn parse_present_counts(input: &mut &str) -> winnow::ModalResult<Vec<u64>> {
         separated(0.., parse_digits, space1).parse_next(input)
     }
 
-    fn parse_tree(input: &mut &str) -> winnow::Result<Tree> {
+    fn parse_tree(input: &mut &str) -> winnow::ModalResult<Tree> {
         seq!(
             Tree {
                 dimensions: parse_dimensions,
-                _: ": ",
+                _: ": ".context(StrContext::Expected(StrContextValue::StringLiteral(": "))),
                 present_counts: parse_present_counts
             }
         )
+        .context(StrContext::Label("tree"))
         .parse_next(input)
     }
 
-    fn parse_trees(input: &mut &str) -> winnow::Result<Vec<Tree>> {
-        separated(0.., parse_tree, multispace1).parse_next(input)
+    fn parse_trees(input: &mut &str) -> winnow::ModalResult<Vec<Tree>> {
+        repeat_till(0.., terminated(cut_err(parse_tree), multispace0), eof)
+            .map(|(trees, _)| trees)
+            .parse_next(input)
     }
 
-    pub fn parse_input(input: &mut &str) -> winnow::Result<Input> {
+    pub fn parse_input(input: &mut &str) -> winnow::ModalResult<Input> {
         seq!(
             Input {
+                _: multispace0,
                 presents: parse_presents,
                 _: multispace1,
                 trees: parse_trees
@@ -286,20 +508,13 @@ mod tests {
 4x4: 0 0 0 0 2 0
 12x5: 1 0 1 0 2 2
 12x5: 1 0 1 0 3 2";
-    const INPUT: &str = include_str!("day12_input.txt");
 
     #[test]
     fn test_example_input() {
-        let result = puzzle(EXAMPLE);
+        let result = puzzle(EXAMPLE).unwrap();
         assert_eq!(result, 2);
     }
 
-    #[test]
-    fn test_input() {
-        let result = puzzle(INPUT);
-        assert_eq!(result, 534);
-    }
-
     #[test]
     #[rstest]
     #[case(
@@ -324,7 +539,39 @@ mod tests {
         1
     )]
     fn test_more_examples(#[case] input: &str, #[case] expected: usize) {
-        let result = puzzle(input);
+        let result = puzzle(input).unwrap();
         assert_eq!(result, expected);
     }
+
+    #[test]
+    #[rstest]
+    #[case("###\n###\n###", 1)]
+    #[case("###\n#..\n###", 4)]
+    #[case("###\n##.\n.##", 8)]
+    fn test_orientations(#[case] shape: &str, #[case] expected: usize) {
+        let shape = parsing::parse_shape.parse(shape).unwrap();
+
+        let orientations = shape.orientations();
+        assert_eq!(orientations.len(), expected);
+        assert!(
+            orientations
+                .iter()
+                .all(|cells| cells.len() == 7 || cells.len() == 9)
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = puzzle("0:\n###\n#.#\n##\n\n4x4: 2").unwrap_err();
+        assert_eq!((error.line(), error.column()), (4, 3));
+        assert_eq!(error.expected(), ["`#`", "`.`"]);
+
+        let error = puzzle("0:\n###\n#.#\n\n4x4: 2").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 1));
+        assert_eq!(error.expected(), ["3x3 shape"]);
+
+        let error = puzzle("0:\n###\n#.#\n###\n\n4x4: 2\n4y4: 1").unwrap_err();
+        assert_eq!((error.line(), error.column()), (7, 2));
+        assert_eq!(error.expected(), ["`x`"]);
+    }
 }
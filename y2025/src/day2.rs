@@ -1,84 +1,289 @@
-use itertools::Itertools;
-use rayon::prelude::*;
+use std::ops::RangeInclusive;
 
-fn two_match(id: &usize) -> bool {
-    if id == &0 {
-        return false;
+use aoc_core::{Answer, ParseError, Solution};
+use rangemap::RangeInclusiveSet;
+use winnow::Parser;
+
+use crate::day2::parsing::parse_ranges;
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Parsed<'a> = Vec<RangeInclusive<usize>>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_ranges
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
     }
 
-    let digit_count = id.ilog10() + 1;
+    fn part1(ranges: &Self::Parsed<'_>) -> Answer {
+        sum_repeated(ranges, Repeats::Exactly(2)).into()
+    }
 
-    if !digit_count.is_multiple_of(2) {
-        return false;
+    fn part2(ranges: &Self::Parsed<'_>) -> Answer {
+        sum_repeated(ranges, Repeats::Any).into()
     }
+}
 
-    let half_base = 10usize.pow(digit_count / 2);
+pub fn puzzle(input: &str) -> Result<(u128, u128), ParseError> {
+    let ranges = Day2::parse(input)?;
 
-    let pattern = id % half_base;
+    Ok((
+        sum_repeated(&ranges, Repeats::Exactly(2)),
+        sum_repeated(&ranges, Repeats::Any),
+    ))
+}
 
-    id / half_base == pattern
+/// Like [`puzzle`], but IDs covered by more than one range are only counted once.
+pub fn puzzle_merged(input: &str) -> Result<(u128, u128), ParseError> {
+    let ranges = merge_overlapping(&Day2::parse(input)?);
+
+    Ok((
+        sum_repeated(&ranges, Repeats::Exactly(2)),
+        sum_repeated(&ranges, Repeats::Any),
+    ))
 }
 
-fn check_all_blocks_match(mut id: usize, pattern: usize, block_base: usize) -> bool {
-    while id > 0 {
-        if id % block_base != pattern {
-            return false;
+/// Merges overlapping and adjacent ranges, sorted by their start.
+pub fn merge_overlapping(ranges: &[RangeInclusive<usize>]) -> Vec<RangeInclusive<usize>> {
+    ranges
+        .iter()
+        .cloned()
+        .collect::<RangeInclusiveSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// How often the block of digits in an ID has to repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeats {
+    /// Made of exactly `k` copies of some block, `222222` is both 3 and 2 copies
+    Exactly(u32),
+    /// Made of `k` or more copies of some block
+    AtLeast(u32),
+    /// Made of at least two copies of some block
+    Any,
+}
+
+impl Repeats {
+    fn min_repeats(&self) -> u32 {
+        match *self {
+            Repeats::Exactly(repeats) | Repeats::AtLeast(repeats) => repeats,
+            Repeats::Any => 2,
         }
-        id /= block_base;
     }
-    true
 }
 
-fn multiple_match(id: &usize) -> bool {
-    if id == &0 {
-        return false;
-    }
+/// The shortest block of digits an ID repeats, e.g. `1212` is `12` twice.
+///
+/// An ID that does not repeat is a single copy of itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatPattern {
+    pub block: u128,
+    pub block_len: u32,
+    pub repeats: u32,
+}
+
+impl RepeatPattern {
+    /// Finds the pattern of `id` written in `radix`, panics if the radix is not in `2..=36`.
+    pub fn of(id: u128, radix: u32) -> Self {
+        assert!((2..=36).contains(&radix), "radix {radix} is not in 2..=36");
+        let radix = u128::from(radix);
+        let digits = id.checked_ilog(radix).unwrap_or(0) + 1;
 
-    let digit_count = id.ilog10() as usize + 1;
+        // the whole ID is always a block, which is the only length whose base could overflow
+        (1..digits)
+            .filter(|block_len| digits.is_multiple_of(*block_len))
+            .find_map(|block_len| {
+                let base = radix.pow(block_len);
+                let block = id % base;
+                let mut rest = id;
+                while rest > 0 {
+                    if rest % base != block {
+                        return None;
+                    }
+                    rest /= base;
+                }
+                Some(Self {
+                    block,
+                    block_len,
+                    repeats: digits / block_len,
+                })
+            })
+            .unwrap_or(Self {
+                block: id,
+                block_len: digits,
+                repeats: 1,
+            })
+    }
 
-    for block_len in 1..=digit_count / 2 {
-        // block must evenly divide the number of digits
-        if !digit_count.is_multiple_of(block_len) {
-            continue;
+    pub fn matches(&self, repeats: Repeats) -> bool {
+        match repeats {
+            // any number of copies of the smallest block forms a larger block
+            Repeats::Exactly(k) => k > 0 && self.repeats.is_multiple_of(k),
+            Repeats::AtLeast(_) | Repeats::Any => self.repeats >= repeats.min_repeats(),
         }
+    }
+}
+
+/// Sums the IDs in `ranges` that are a block of digits repeated, without visiting every ID.
+///
+/// An ID with `digits` digits made of `repeats` copies of a block `p` is `p * R`, with `R` the
+/// repunit `1 0..01 0..01`. The blocks inside a range form a range themselves, so every block
+/// length adds up as an arithmetic series.
+fn sum_repeated(ranges: &[RangeInclusive<usize>], repeats: Repeats) -> u128 {
+    ranges
+        .iter()
+        .map(|range| {
+            let (start, end) = (*range.start() as u128, *range.end() as u128);
+            (digit_count(start)..=digit_count(end))
+                .map(|digits| {
+                    let lowest = 10u128.pow(digits - 1);
+                    let range = start.max(lowest)..=end.min(lowest * 10 - 1);
+                    match repeats {
+                        Repeats::Exactly(k) if k > 0 && digits.is_multiple_of(k) => {
+                            sum_periodic(&range, digits, digits / k)
+                        }
+                        Repeats::Exactly(_) => 0,
+                        Repeats::AtLeast(_) | Repeats::Any => {
+                            sum_at_least(&range, digits, repeats.min_repeats())
+                        }
+                    }
+                })
+                .sum::<u128>()
+        })
+        .sum()
+}
+
+fn digit_count(id: u128) -> u32 {
+    id.checked_ilog10().unwrap_or(0) + 1
+}
 
-        let block_base = 10usize.pow(block_len as u32);
-        // get the pattern for the block size
-        let pattern = id % block_base;
+// An ID made of `k` copies of a block is also made of copies for every multiple `f` of `k` that
+// divides `digits`, e.g. `222222` is `222` twice but also `22` three times and `2` six times.
+// The IDs whose smallest block repeats exactly `k` times are found with the Möbius function over
+// those multiples, and summing them for every `k >= min_repeats` counts each ID once.
+fn sum_at_least(range: &RangeInclusive<u128>, digits: u32, min_repeats: u32) -> u128 {
+    let divisors: Vec<_> = (1..=digits)
+        .filter(|repeats| digits.is_multiple_of(*repeats))
+        .collect();
 
-        if check_all_blocks_match(*id, pattern, block_base) {
-            return true;
+    let (mut added, mut removed) = (0, 0);
+    for &repeats in divisors.iter().filter(|repeats| **repeats >= min_repeats) {
+        for &multiple in divisors.iter().filter(|f| f.is_multiple_of(repeats)) {
+            match mobius(multiple / repeats) {
+                1 => added += sum_periodic(range, digits, digits / multiple),
+                -1 => removed += sum_periodic(range, digits, digits / multiple),
+                _ => {}
+            }
         }
     }
+    added - removed
+}
 
-    false
+/// The sum of the IDs in `range` with `digits` digits made of a repeated `block_len` digit block.
+fn sum_periodic(range: &RangeInclusive<u128>, digits: u32, block_len: u32) -> u128 {
+    let repunit = (10u128.pow(digits) - 1) / (10u128.pow(block_len) - 1);
+    let first = range
+        .start()
+        .div_ceil(repunit)
+        .max(10u128.pow(block_len - 1));
+    let last = (range.end() / repunit).min(10u128.pow(block_len) - 1);
+    if first > last {
+        return 0;
+    }
+
+    repunit * (first + last) * (last - first + 1) / 2
 }
 
-pub fn puzzle(input: &str) -> (usize, usize) {
-    input
-        .par_split(',')
-        .map(move |ids| {
-            let (start, end) = ids
-                .splitn(2, '-')
-                .map(|n| n.parse::<usize>().unwrap_or_default())
-                .collect_tuple()
-                .unwrap_or_default();
-
-            (
-                (start..=end)
-                    .into_par_iter()
-                    .filter(two_match)
-                    .sum::<usize>(),
-                (start..=end)
-                    .into_par_iter()
-                    .filter(multiple_match)
-                    .sum::<usize>(),
-            )
-        })
-        .reduce(
-            || (0, 0),
-            |result, acc| (result.0 + acc.0, result.1 + acc.1),
+fn mobius(mut n: u32) -> i8 {
+    let mut sign = 1;
+    let mut factor = 2;
+    while factor * factor <= n {
+        if n.is_multiple_of(factor) {
+            n /= factor;
+            if n.is_multiple_of(factor) {
+                return 0;
+            }
+            sign = -sign;
+        }
+        factor += 1;
+    }
+    if n > 1 { -sign } else { sign }
+}
+
+mod parsing {
+    use std::ops::RangeInclusive;
+
+    use winnow::{
+        ascii::{digit1, multispace0, multispace1},
+        combinator::{alt, cut_err, delimited, fail, opt, preceded, repeat, seq},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+        stream::Stream,
+    };
+
+    fn parse_id(input: &mut &str) -> winnow::ModalResult<usize> {
+        digit1
+            .try_map(str::parse)
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
+    }
+
+    fn parse_range(input: &mut &str) -> winnow::ModalResult<RangeInclusive<usize>> {
+        let start = input.checkpoint();
+        let (first, last) = seq!(
+            parse_id,
+            _: cut_err('-'.context(StrContext::Expected(StrContextValue::CharLiteral('-')))),
+            cut_err(parse_id)
+        )
+        .context(StrContext::Label("range"))
+        .parse_next(input)?;
+
+        if last < first {
+            input.reset(&start);
+            return cut_err(fail)
+                .context(StrContext::Label("range"))
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "end not before start",
+                )))
+                .parse_next(input);
+        }
+
+        Ok(first..=last)
+    }
+
+    // a comma commits to another range, while plain whitespace may just be the end of the list
+    fn parse_next_range(input: &mut &str) -> winnow::ModalResult<RangeInclusive<usize>> {
+        alt((
+            preceded(
+                (multispace0, ','),
+                cut_err(preceded(multispace0, parse_range)),
+            ),
+            preceded(multispace1, parse_range),
+        ))
+        .parse_next(input)
+    }
+
+    /// Ranges separated by commas and/or whitespace, including line breaks.
+    pub fn parse_ranges(input: &mut &str) -> winnow::ModalResult<Vec<RangeInclusive<usize>>> {
+        delimited(
+            multispace0,
+            opt((parse_range, repeat(0.., parse_next_range))),
+            multispace0,
         )
+        .map(|ranges: Option<(_, Vec<_>)>| {
+            let Some((first, mut rest)) = ranges else {
+                return Vec::new();
+            };
+            rest.insert(0, first);
+            rest
+        })
+        .parse_next(input)
+    }
 }
 
 #[cfg(test)]
@@ -88,6 +293,59 @@ mod tests {
 
     use super::*;
 
+    fn two_match(id: &usize) -> bool {
+        if id == &0 {
+            return false;
+        }
+
+        let digit_count = id.ilog10() + 1;
+
+        if !digit_count.is_multiple_of(2) {
+            return false;
+        }
+
+        let half_base = 10usize.pow(digit_count / 2);
+
+        let pattern = id % half_base;
+
+        id / half_base == pattern
+    }
+
+    fn check_all_blocks_match(mut id: usize, pattern: usize, block_base: usize) -> bool {
+        while id > 0 {
+            if id % block_base != pattern {
+                return false;
+            }
+            id /= block_base;
+        }
+        true
+    }
+
+    fn multiple_match(id: &usize) -> bool {
+        if id == &0 {
+            return false;
+        }
+
+        let digit_count = id.ilog10() as usize + 1;
+
+        for block_len in 1..=digit_count / 2 {
+            // block must evenly divide the number of digits
+            if !digit_count.is_multiple_of(block_len) {
+                continue;
+            }
+
+            let block_base = 10usize.pow(block_len as u32);
+            // get the pattern for the block size
+            let pattern = id % block_base;
+
+            if check_all_blocks_match(*id, pattern, block_base) {
+                return true;
+            }
+        }
+
+        false
+    }
+
     #[test]
     #[rstest]
     #[case(0, false)]
@@ -136,9 +394,135 @@ mod tests {
         assert_eq!(multiple_match(&input), expected);
     }
 
+    #[test]
+    #[rstest]
+    #[case(0..=1_000_000)]
+    #[case(95..=115)]
+    #[case(222_220..=222_224)]
+    #[case(11_111_000..=11_112_000)]
+    #[case(123_123_000..=123_124_000)]
+    #[case(999_999_990..=1_000_000_020)]
+    fn test_sum_repeated_matches_enumeration(#[case] range: RangeInclusive<usize>) {
+        let enumerated = |matches: fn(&usize) -> bool| {
+            range
+                .clone()
+                .filter(matches)
+                .map(|id| id as u128)
+                .sum::<u128>()
+        };
+        let ranges = [range.clone()];
+
+        assert_eq!(
+            sum_repeated(&ranges, Repeats::Exactly(2)),
+            enumerated(two_match)
+        );
+        assert_eq!(
+            sum_repeated(&ranges, Repeats::Any),
+            enumerated(multiple_match)
+        );
+
+        for repeats in [
+            Repeats::Exactly(3),
+            Repeats::AtLeast(3),
+            Repeats::AtLeast(1),
+        ] {
+            let enumerated = range
+                .clone()
+                .map(|id| id as u128)
+                .filter(|id| RepeatPattern::of(*id, 10).matches(repeats))
+                .sum::<u128>();
+            assert_eq!(sum_repeated(&ranges, repeats), enumerated, "{repeats:?}");
+        }
+    }
+
+    #[test]
+    #[rstest]
+    #[case(1188511885, 10, 11885, 5, 2)]
+    #[case(222222, 10, 2, 1, 6)]
+    #[case(123123123, 10, 123, 3, 3)]
+    #[case(1698522, 10, 1698522, 7, 1)]
+    #[case(0, 10, 0, 1, 1)]
+    #[case(0b1010_1010, 2, 0b10, 2, 4)]
+    #[case(0b1001, 2, 0b1001, 4, 1)]
+    #[case(0xabcabc, 16, 0xabc, 3, 2)]
+    #[case(0xffff_ffff, 16, 0xf, 1, 8)]
+    #[case(u128::MAX, 2, 1, 1, 128)]
+    fn test_repeat_pattern(
+        #[case] id: u128,
+        #[case] radix: u32,
+        #[case] block: u128,
+        #[case] block_len: u32,
+        #[case] repeats: u32,
+    ) {
+        assert_eq!(
+            RepeatPattern::of(id, radix),
+            RepeatPattern {
+                block,
+                block_len,
+                repeats
+            }
+        );
+    }
+
+    #[test]
+    #[rstest]
+    #[case(222222, Repeats::Exactly(2), true)]
+    #[case(222222, Repeats::Exactly(3), true)]
+    #[case(222222, Repeats::Exactly(4), false)]
+    #[case(222222, Repeats::AtLeast(6), true)]
+    #[case(222222, Repeats::AtLeast(7), false)]
+    #[case(123123123, Repeats::Exactly(2), false)]
+    #[case(123123123, Repeats::Any, true)]
+    #[case(1698522, Repeats::Any, false)]
+    #[case(1698522, Repeats::Exactly(1), true)]
+    fn test_repeat_pattern_matches(
+        #[case] id: u128,
+        #[case] repeats: Repeats,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(RepeatPattern::of(id, 10).matches(repeats), expected);
+    }
+
+    #[test]
+    fn test_repeat_pattern_agrees_with_matchers() {
+        for id in 0..200_000usize {
+            let pattern = RepeatPattern::of(id as u128, 10);
+            assert_eq!(pattern.matches(Repeats::Exactly(2)), two_match(&id), "{id}");
+            assert_eq!(pattern.matches(Repeats::Any), multiple_match(&id), "{id}");
+        }
+    }
+
+    #[test]
+    fn test_sum_repeated_wide_range() {
+        // every ID up to 18 digits, far too many to enumerate
+        let ranges = [1..=999_999_999_999_999_999];
+
+        // the doubled blocks of `half` digits are `p * (10^half + 1)` for every `half` digit `p`
+        let doubled = (1..=9u32)
+            .map(|half| {
+                let (first, last) = (10u128.pow(half - 1), 10u128.pow(half) - 1);
+                (last + 2) * (first + last) * (last - first + 1) / 2
+            })
+            .sum::<u128>();
+        assert_eq!(sum_repeated(&ranges, Repeats::Exactly(2)), doubled);
+        assert!(sum_repeated(&ranges, Repeats::Any) > doubled);
+    }
+
+    #[test]
+    #[rstest]
+    #[case(1, 1)]
+    #[case(2, -1)]
+    #[case(4, 0)]
+    #[case(6, 1)]
+    #[case(12, 0)]
+    #[case(30, -1)]
+    fn test_mobius(#[case] n: u32, #[case] expected: i8) {
+        assert_eq!(mobius(n), expected);
+    }
+
     #[test]
     fn test_empty_input() {
-        let result = puzzle("");
+        let result = puzzle("").unwrap();
         assert_eq!(result, (0, 0));
     }
 
@@ -146,13 +530,48 @@ mod tests {
     fn test_example_input() {
         let result = puzzle(
             "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (1227775554, 4174379265));
     }
 
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day2_input.txt"));
-        assert_eq!(result, (24157613387, 33832678380));
+    fn test_invalid_input() {
+        let error = puzzle("11-22,95-").unwrap_err();
+        assert_eq!((error.line(), error.column()), (1, 10));
+        assert_eq!(error.expected(), ["digit"]);
+
+        let error = puzzle("11-22,95+115").unwrap_err();
+        assert_eq!((error.line(), error.column()), (1, 9));
+        assert_eq!(error.expected(), ["`-`"]);
+
+        let error = puzzle("11-22,\n115-95").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 1));
+        assert_eq!(error.expected(), ["end not before start"]);
+
+        let error = puzzle("11-22, ,95-115").unwrap_err();
+        assert_eq!((error.line(), error.column()), (1, 8));
+        assert_eq!(error.expected(), ["digit"]);
+    }
+
+    #[test]
+    #[rstest]
+    #[case("11-22,95-115", vec![11..=22, 95..=115])]
+    #[case("  11-22 ,\n 95-115\n", vec![11..=22, 95..=115])]
+    #[case("11-22\n95-115\n", vec![11..=22, 95..=115])]
+    #[case("7-7", vec![7..=7])]
+    #[case(" \n", vec![])]
+    fn test_parse_ranges(#[case] input: &str, #[case] expected: Vec<RangeInclusive<usize>>) {
+        assert_eq!(Day2::parse(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_merged() {
+        let ranges = Day2::parse("95-115,11-22,100-120,22-33,34-40").unwrap();
+        assert_eq!(merge_overlapping(&ranges), [11..=40, 95..=120]);
+
+        // 99 is inside both 95-115 and 99-101
+        assert_eq!(puzzle("95-115,99-101").unwrap(), (198, 309));
+        assert_eq!(puzzle_merged("95-115,99-101").unwrap(), (99, 210));
     }
 }
@@ -1,62 +1,190 @@
+use std::fmt;
+
+use aoc_core::{Answer, ParseError, Solution};
 use rayon::prelude::*;
 use tracing::{debug, info};
+use winnow::Parser;
+
+use crate::day3::parsing::parse_banks;
+
+/// The digits picked from a bank, in their original order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub indices: Vec<usize>,
+    digits: Vec<u8>,
+}
+
+impl Selection {
+    /// The selected ASCII digits.
+    pub fn digits(&self) -> &[u8] {
+        &self.digits
+    }
+
+    /// The number formed by the digits, `None` if it does not fit a `u128` (more than 38 digits).
+    pub fn value(&self) -> Option<u128> {
+        self.digits.iter().try_fold(0u128, |value, digit| {
+            let digit = char::from(*digit).to_digit(10)?;
+            value.checked_mul(10)?.checked_add(u128::from(digit))
+        })
+    }
+}
 
-fn calculate_joltage<const N: usize>(input: &str) -> u64 {
-    let bytes = input.trim().as_bytes();
-    if bytes.len() < N {
-        return byte_digts_to_number(bytes);
-    }
-
-    let mut digits = [0u8; N];
-    let mut previous_digit_idx: usize = 0;
-    info!(?bytes);
-    for idx in 0..N {
-        debug!(?idx, ?digits, ?previous_digit_idx);
-        let slice_end = bytes.len() - (N - idx - 1);
-        let slice = &bytes[previous_digit_idx..slice_end];
-        let (digit_idx, max) = slice.iter().enumerate().rev().max_by_key(|n| n.1).unwrap();
-        debug!(?slice, ?digit_idx, ?max);
-        if digit_idx == (slice.len() - 1) {
-            info!("Skipping rest");
-            digits[idx..].copy_from_slice(&bytes[previous_digit_idx + digit_idx..]);
-            debug!(?digits);
-            break;
+// the digits are the number at any length, for selections too large for `value`
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(str::from_utf8(&self.digits).map_err(|_| fmt::Error)?)
+    }
+}
+
+/// Picks the `k` digits of `bytes` that, kept in order, form the largest number.
+///
+/// Keeps a stack of picked digits that is non-increasing where possible: a digit drops smaller
+/// ones before it for as long as enough digits are left to still pick `k`. Banks shorter than
+/// `k` are picked whole. `None` if any byte is not an ASCII digit.
+pub fn select_max_subsequence(bytes: &[u8], k: usize) -> Option<Selection> {
+    if !bytes.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let mut droppable = bytes.len().saturating_sub(k);
+    let mut stack: Vec<usize> = Vec::with_capacity(bytes.len());
+    for (idx, digit) in bytes.iter().enumerate() {
+        while droppable > 0 && stack.last().is_some_and(|top| bytes[*top] < *digit) {
+            stack.pop();
+            droppable -= 1;
         }
-        previous_digit_idx += digit_idx + 1;
-        digits[idx] = *max;
+        stack.push(idx);
     }
+    stack.truncate(k);
 
-    let num = byte_digts_to_number(&digits);
+    debug!(?bytes, ?stack);
+    Some(Selection {
+        digits: stack.iter().map(|idx| bytes[*idx]).collect(),
+        indices: stack,
+    })
+}
 
-    info!("'{input}' -> '{num}");
-    num
+fn calculate_joltage(bank: &str, batteries: usize) -> u128 {
+    let selection =
+        select_max_subsequence(bank.as_bytes(), batteries).expect("parsed banks are only digits");
+    info!("'{bank}' -> '{selection}'");
+    selection
+        .value()
+        .expect("joltages of up to 38 batteries fit a u128")
 }
 
-fn byte_digts_to_number(digits: &[u8]) -> u64 {
-    digits
-        .iter()
-        .rev()
-        .enumerate()
-        .map(|(idx, digit)| {
-            let num = (digit & 0x0F) as u64;
-            10u64.pow(idx as u32) * num
-        })
-        .sum()
+/// The total joltage of every bank with `batteries` turned on, `None` if it does not fit a `u128`
+/// or a bank is not only digits.
+pub fn total_joltage(banks: &[&str], batteries: usize) -> Option<u128> {
+    banks
+        .par_iter()
+        .map(|bank| select_max_subsequence(bank.as_bytes(), batteries)?.value())
+        .try_reduce(|| 0, |a, b| a.checked_add(b))
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Parsed<'a> = Vec<&'a str>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        if let Some(banks) = parsing::split_banks(input) {
+            return Ok(banks);
+        }
+
+        // only invalid input gets here, the parser reports where
+        parse_banks
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(banks: &Self::Parsed<'_>) -> Answer {
+        banks
+            .par_iter()
+            .map(|bank| calculate_joltage(bank, 2))
+            .sum::<u128>()
+            .into()
+    }
+
+    fn part2(banks: &Self::Parsed<'_>) -> Answer {
+        banks
+            .par_iter()
+            .map(|bank| calculate_joltage(bank, 12))
+            .sum::<u128>()
+            .into()
+    }
+}
+
+fn calculate_both(input: &str) -> (u128, u128) {
+    (calculate_joltage(input, 2), calculate_joltage(input, 12))
 }
 
-fn calculate_both(input: &str) -> (u64, u64) {
-    (
-        calculate_joltage::<2>(input),
-        calculate_joltage::<12>(input),
-    )
+pub fn puzzle(input: &str) -> Result<(u128, u128), ParseError> {
+    Ok(Day3::parse(input)?
+        .par_iter()
+        .map(|bank| calculate_both(bank))
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1)))
 }
 
-pub fn puzzle(input: &str) -> (u64, u64) {
-    input
-        .trim()
-        .par_lines()
-        .map(calculate_both)
-        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
+mod parsing {
+    use winnow::{
+        ascii::{digit1, line_ending, space0},
+        combinator::{alt, cut_err, eof, repeat_till},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+    };
+
+    const CHUNK: usize = 64;
+
+    // no early exit inside a chunk, so the check compiles down to vector compares
+    fn all_digits(bytes: &[u8]) -> bool {
+        bytes.chunks(CHUNK).all(|chunk| {
+            chunk
+                .iter()
+                .fold(true, |all, byte| all & byte.is_ascii_digit())
+        })
+    }
+
+    /// Fast path for valid input, the same banks as [`parse_banks`] or `None` if any line is not
+    /// a bank or blank.
+    pub fn split_banks(input: &str) -> Option<Vec<&str>> {
+        let mut lines = input.split('\n').peekable();
+        let mut banks = Vec::new();
+        while let Some(mut line) = lines.next() {
+            // only a `\r` followed by a line feed ends a line
+            if lines.peek().is_some() {
+                line = line.strip_suffix('\r').unwrap_or(line);
+            }
+
+            if all_digits(line.as_bytes()) {
+                if !line.is_empty() {
+                    banks.push(line);
+                }
+            } else if !line.bytes().all(|byte| byte == b' ' || byte == b'\t') {
+                return None;
+            }
+        }
+        Some(banks)
+    }
+
+    fn parse_bank<'a>(input: &mut &'a str) -> winnow::ModalResult<Option<&'a str>> {
+        let blank = (space0, alt((line_ending, eof))).value(None);
+        let bank = (digit1, cut_err(alt((line_ending, eof)))).map(|(bank, _)| Some(bank));
+
+        cut_err(alt((blank, bank)))
+            .context(StrContext::Label("battery bank"))
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
+    }
+
+    /// One bank of digits per line, blank lines are skipped.
+    pub fn parse_banks<'a>(input: &mut &'a str) -> winnow::ModalResult<Vec<&'a str>> {
+        repeat_till(0.., parse_bank, eof)
+            .map(|(banks, _): (Vec<_>, _)| banks.into_iter().flatten().collect())
+            .parse_next(input)
+    }
 }
 
 #[cfg(test)]
@@ -75,9 +203,10 @@ mod tests {
     #[case("999999999999998", 99)]
     #[case("11", 11)]
     #[case("1", 1)]
-    fn test_calculate_joltage_2(#[case] input: &str, #[case] expected: u64) {
-        assert_eq!(calculate_joltage::<2>(input), expected);
+    fn test_calculate_joltage_2(#[case] input: &str, #[case] expected: u128) {
+        assert_eq!(calculate_joltage(input, 2), expected);
     }
+
     #[test]
     #[rstest]
     #[case("987654321111111", 987654321111)]
@@ -87,13 +216,64 @@ mod tests {
     #[case("999999999999998", 999999999999)]
     #[case("11", 11)]
     #[case("1", 1)]
-    fn test_calculate_joltage_12(#[case] input: &str, #[case] expected: u64) {
-        assert_eq!(calculate_joltage::<12>(input), expected);
+    fn test_calculate_joltage_12(#[case] input: &str, #[case] expected: u128) {
+        assert_eq!(calculate_joltage(input, 12), expected);
+    }
+
+    #[test]
+    #[rstest]
+    #[case("818181911112111", 2, vec![6, 11])]
+    #[case("818181911112111", 12, vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14])]
+    #[case("987654321111111", 0, vec![])]
+    #[case("1", 3, vec![0])]
+    #[case("9191", 2, vec![0, 2])]
+    fn test_select_indices(#[case] input: &str, #[case] k: usize, #[case] expected: Vec<usize>) {
+        assert_eq!(
+            select_max_subsequence(input.as_bytes(), k).unwrap().indices,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_select_large_counts() {
+        let bank = "1234567890".repeat(5);
+
+        let selection = select_max_subsequence(bank.as_bytes(), 25).unwrap();
+        assert_eq!(selection.value(), Some(9989012345678901234567890));
+
+        // more digits than a u128 holds are still available as text
+        let selection = select_max_subsequence(bank.as_bytes(), 40).unwrap();
+        assert_eq!(selection.value(), None);
+        assert_eq!(
+            selection.to_string(),
+            "9234567890123456789012345678901234567890"
+        );
+    }
+
+    #[test]
+    fn test_total_joltage() {
+        let banks = Day3::parse("987654321111111\n811111111111119").unwrap();
+        assert_eq!(total_joltage(&banks, 2), Some(98 + 89));
+        assert_eq!(
+            total_joltage(&banks, 15),
+            Some(987654321111111 + 811111111111119)
+        );
+        assert_eq!(total_joltage(&["9".repeat(39).as_str()], 39), None);
+        assert_eq!(total_joltage(&["98/7"], 2), None);
+    }
+
+    #[test]
+    #[rstest]
+    #[case(b"12/3")]
+    #[case(b"12:3")]
+    #[case(b"9\xff")]
+    fn test_select_rejects_non_digits(#[case] bytes: &[u8]) {
+        assert_eq!(select_max_subsequence(bytes, 2), None);
     }
 
     #[test]
     fn test_empty_input() {
-        let result = puzzle("");
+        let result = puzzle("").unwrap();
         assert_eq!(result, (0, 0));
     }
 
@@ -104,13 +284,59 @@ mod tests {
 811111111111119
 234234234234278
 818181911112111",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (357, 3121910778619));
     }
 
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day3_input.txt"));
-        assert_eq!(result, (17155, 169685670469164));
+    fn test_invalid_input() {
+        let error = puzzle("987654321111111\n81111x111111119").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 6));
+        assert_eq!(error.expected(), ["digit"]);
+
+        // a carriage return only ends a line together with a line feed
+        let error = puzzle("987654321111111\r\n81111\r111111119").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 6));
+        assert_eq!(error.expected(), ["digit"]);
+
+        let error = puzzle("987654321111111\n\n  811111111111119").unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 1));
+
+        let error = puzzle("987654321111111 \n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (1, 16));
+    }
+
+    #[test]
+    #[rstest]
+    #[case("", vec![])]
+    #[case("\n \n\t\n", vec![])]
+    #[case("123\n456", vec!["123", "456"])]
+    #[case("123\r\n\r\n456\r\n", vec!["123", "456"])]
+    #[case("\n123\n\n456\n\n", vec!["123", "456"])]
+    fn test_parse_banks(#[case] input: &str, #[case] expected: Vec<&str>) {
+        assert_eq!(parsing::split_banks(input), Some(expected.clone()));
+        assert_eq!(parse_banks.parse(input).unwrap(), expected);
+    }
+
+    #[test]
+    #[rstest]
+    #[case("12a")]
+    #[case("12\r")]
+    #[case("12\n 3")]
+    #[case(&format!("{}x{}", "1".repeat(100), "2".repeat(100)))]
+    fn test_split_banks_rejects(#[case] input: &str) {
+        assert_eq!(parsing::split_banks(input), None);
+        assert!(parse_banks.parse(input).is_err());
+    }
+
+    #[test]
+    fn test_long_bank() {
+        let bank = "3141592653589793".repeat(1 << 16);
+        let input = format!("{bank}\n{bank}\n");
+
+        let banks = Day3::parse(&input).unwrap();
+        assert_eq!(banks.len(), 2);
+        assert_eq!(calculate_joltage(banks[0], 12), 999999999999);
     }
 }
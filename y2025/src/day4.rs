@@ -1,72 +1,327 @@
-use ndarray::{Array2, array};
-use ndarray_conv::{ConvExt, ConvMode, PaddingMode};
+use std::{
+    collections::HashSet,
+    fmt::{self, Write},
+};
+
+use aoc_core::{
+    Answer, Grid, ParseError, Solution,
+    automaton::{Automaton, Boundary, Kernel, Rule, Update},
+    grid::Position,
+    visualise::{BLACK, FrameWriter, Rgb, VisualiseError, WHITE},
+};
+
+use ndarray::Array2;
 use tracing::{debug, info};
+use winnow::Parser;
+
+use crate::day4::parsing::parse_paper;
+
+pub struct Day4;
 
-pub fn puzzle(input: &str) -> (usize, usize) {
-    if input.is_empty() {
-        return (0, 0);
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Parsed<'a> = Paper;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_paper
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
     }
 
-    let mut input = transform_str_to_ndarray(input);
-    info!("{input:?}");
+    fn part1(input: &Self::Parsed<'_>) -> Answer {
+        input
+            .removal_rounds()
+            .next()
+            .map_or(0, |removed| removed.len())
+            .into()
+    }
 
-    let kernel = array![[1u8, 1u8, 1u8], [1u8, 0u8, 1u8], [1u8, 1u8, 1u8]];
+    fn part2(input: &Self::Parsed<'_>) -> Answer {
+        input
+            .removal_rounds()
+            .map(|removed| removed.len())
+            .sum::<usize>()
+            .into()
+    }
+}
+
+pub fn puzzle(input: &str) -> Result<Removal, ParseError> {
+    let removal = Removal::new(Day4::parse(input)?);
+    // sparse paper is only stored for huge sheets, too big to print
+    match &removal.paper {
+        Paper::Dense(_) => {
+            info!("\n{}", removal.paper);
+            debug!("\n{}", removal.render().unwrap_or_default());
+        }
+        Paper::Sparse {
+            rows,
+            columns,
+            rolls,
+        } => info!("{} rolls on {rows}x{columns} cells", rolls.len()),
+    }
 
-    let part1_result = remove_paper(&mut input, &kernel);
+    Ok(removal)
+}
 
-    let mut removed = part1_result;
-    let mut total = removed;
-    while removed > 0 {
-        removed = remove_paper(&mut input, &kernel);
-        total += removed;
+/// How the paper was removed, to audit the answers with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removal {
+    /// The rolls removed in the first round (part 1)
+    pub first_round: usize,
+    /// The rolls removed over all rounds (part 2)
+    pub total: usize,
+    /// How many rounds removed paper until the grid was stable
+    pub rounds: u32,
+    paper: Paper,
+}
+
+impl Removal {
+    pub fn new(paper: Paper) -> Self {
+        let mut removed_per_round = Vec::new();
+        let mut rounds = 0;
+        for (round, removed) in (1..).zip(paper.removal_rounds()) {
+            removed_per_round.push(removed.len());
+            rounds = round;
+        }
+
+        Self {
+            first_round: removed_per_round.first().copied().unwrap_or(0),
+            total: removed_per_round.iter().sum(),
+            rounds,
+            paper,
+        }
+    }
+
+    /// The round every cell was removed in, starting at 1, `None` for rolls that are never
+    /// removed and cells without paper. The rounds are removed again to find out, and like
+    /// [`Paper::to_grid`] there is no grid for sparse paper that is too large.
+    pub fn removed_in(&self) -> Option<Array2<Option<u32>>> {
+        let mut removed_in = self.paper.to_grid()?.map(|_| None).into_array();
+        for (round, removed) in (1..).zip(self.paper.removal_rounds()) {
+            for position in removed {
+                removed_in[position] = Some(round);
+            }
+        }
+        Some(removed_in)
     }
 
-    (part1_result, total)
+    /// The grid with every removed roll labelled by its round: `1`-`9`, then `a`-`z` for rounds
+    /// 10 to 35 and `+` after that. Rolls that stay are `@` and empty cells `.`.
+    pub fn render(&self) -> Option<String> {
+        let removed_in = self.removed_in()?;
+        let labels =
+            Array2::from_shape_fn(removed_in.dim(), |position| match removed_in[position] {
+                Some(round) => char::from_digit(round, 36).unwrap_or('+'),
+                None if self.paper.contains(position) => '@',
+                None => '.',
+            });
+        Some(Grid::new(labels).render(|label| *label))
+    }
 }
 
-fn remove_paper(input: &mut Array2<u8>, kernal: &Array2<u8>) -> usize {
-    let sums = input
-        .conv(kernal, ConvMode::Same, PaddingMode::Zeros)
-        .unwrap();
+/// Writes the grid before the first and after every removal round, with the rolls removed in
+/// that round highlighted.
+pub fn visualise(input: &str, frames: &mut FrameWriter) -> Result<(), VisualiseError> {
+    const REMOVED: Rgb = [220, 50, 50];
 
-    debug!("{sums:?}");
+    let paper = Day4::parse(input)?;
+    let grid = paper.to_grid().ok_or(VisualiseError::TooLarge {
+        rows: paper.rows(),
+        columns: paper.columns(),
+    })?;
+    let mut colours = grid.map(|roll| if *roll { BLACK } else { WHITE });
+    frames.write(&colours, |colour| *colour)?;
 
-    let mut removed = 0;
-    for (input, conv) in input.iter_mut().zip(sums.iter()) {
-        if *input == 1 && *conv < 4 {
-            *input = 0;
-            removed += 1;
+    let mut highlighted = Vec::new();
+    for removed in paper.removal_rounds() {
+        for position in highlighted {
+            colours[position] = WHITE;
+        }
+        for position in &removed {
+            colours[*position] = REMOVED;
         }
+        frames.write(&colours, |colour| *colour)?;
+        highlighted = removed;
     }
 
-    removed
+    Ok(())
 }
 
-fn transform_str_to_ndarray(input: &str) -> Array2<u8> {
-    let rows = input.lines().count();
-    let cols = input.lines().next().unwrap().len();
+/// A roll of paper is removed once fewer than this many of its 8 neighbours are paper.
+const MIN_NEIGHBOURS: u8 = 4;
+
+/// Grids with fewer rolls than one in this many cells only store the rolls.
+const SPARSE_DENSITY: usize = 32;
 
-    let mut data = Vec::with_capacity(rows * cols);
+/// Sparse paper is only turned into a grid of every cell up to this many cells.
+const MAX_GRID_CELLS: usize = 1 << 28;
 
-    for line in input.lines() {
-        for c in line.bytes() {
-            data.push((c == b'@') as u8);
+/// Where the rolls of paper are, stored to suit how much of the grid they cover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Paper {
+    /// Every cell, `true` for a roll
+    Dense(Grid<bool>),
+    /// Only the rolls, for huge sheets that are mostly empty
+    Sparse {
+        rows: usize,
+        columns: usize,
+        rolls: HashSet<Position>,
+    },
+}
+
+impl Paper {
+    /// The paper from rows with `true` for a roll, only storing the rolls if they are fewer
+    /// than one in [`SPARSE_DENSITY`] cells. Shorter rows are empty at the end.
+    pub fn from_rows(rows: Vec<Vec<bool>>) -> Self {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let rolls: usize = rows.iter().flatten().filter(|roll| **roll).count();
+        if rolls * SPARSE_DENSITY < rows.len() * columns {
+            let rolls = rows.iter().enumerate().flat_map(|(row, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, roll)| **roll)
+                    .map(move |(column, _)| (row, column))
+            });
+            return Paper::Sparse {
+                rows: rows.len(),
+                columns,
+                rolls: rolls.collect(),
+            };
+        }
+
+        Paper::Dense(Grid::from_ragged_rows(rows, false))
+    }
+
+    pub fn rows(&self) -> usize {
+        match self {
+            Paper::Dense(grid) => grid.rows(),
+            Paper::Sparse { rows, .. } => *rows,
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        match self {
+            Paper::Dense(grid) => grid.columns(),
+            Paper::Sparse { columns, .. } => *columns,
+        }
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        match self {
+            Paper::Dense(grid) => grid.get(position).copied().unwrap_or(false),
+            Paper::Sparse { rolls, .. } => rolls.contains(&position),
         }
     }
 
-    Array2::from_shape_vec((rows, cols), data).unwrap()
+    /// Every cell of the paper, `None` for sparse paper with more than [`MAX_GRID_CELLS`]
+    /// cells, which is too large to hold cell by cell.
+    pub fn to_grid(&self) -> Option<Grid<bool>> {
+        match self {
+            Paper::Dense(grid) => Some(grid.clone()),
+            Paper::Sparse { rows, columns, .. } if rows * columns > MAX_GRID_CELLS => None,
+            Paper::Sparse { rows, columns, .. } => Some(Grid::new(Array2::from_shape_fn(
+                (*rows, *columns),
+                |position| self.contains(position),
+            ))),
+        }
+    }
+
+    /// Removes the accessible paper round by round, every generation of the automaton yields
+    /// the positions removed in that round until no more paper can be removed.
+    pub fn removal_rounds(&self) -> Automaton {
+        let rule = Rule::Removal {
+            min: MIN_NEIGHBOURS.into(),
+        };
+        let automaton = match self {
+            Paper::Dense(grid) => Automaton::new(grid.clone(), rule),
+            Paper::Sparse {
+                rows,
+                columns,
+                rolls,
+            } => Automaton::sparse(*rows, *columns, rolls.iter().copied().collect(), rule)
+                .expect("a removal rule never needs the empty cells"),
+        };
+        automaton
+            .with_kernel(Kernel::moore())
+            .with_boundary(Boundary::Zeros)
+            .with_update(Update::Synchronous)
+    }
+}
+
+impl fmt::Display for Paper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.rows() {
+            if row > 0 {
+                f.write_char('\n')?;
+            }
+            for column in 0..self.columns() {
+                let cell = if self.contains((row, column)) {
+                    '@'
+                } else {
+                    '.'
+                };
+                f.write_char(cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+mod parsing {
+    use aoc_core::grid::ragged_rows;
+    use winnow::{
+        ascii::multispace0,
+        combinator::{cut_err, fail, terminated},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+        token::one_of,
+    };
+
+    use super::Paper;
+
+    fn parse_cell(input: &mut &str) -> ModalResult<bool> {
+        one_of(['.', '@'])
+            .map(|cell| cell == '@')
+            .context(StrContext::Label("cell"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('.')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('@')))
+            .parse_next(input)
+    }
+
+    /// Rows may differ in length, the cells missing at the end of the shorter ones are empty.
+    pub fn parse_paper(input: &mut &str) -> ModalResult<Paper> {
+        if input.trim().is_empty() {
+            return cut_err(fail)
+                .context(StrContext::Label("paper"))
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "a row of `.` and `@`",
+                )))
+                .parse_next(input);
+        }
+
+        terminated(ragged_rows(parse_cell), multispace0)
+            .map(Paper::from_rows)
+            .parse_next(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use rstest::rstest;
     use test_log::test;
 
     use super::*;
 
     #[test]
-    fn test_empty_input() {
-        let result = puzzle("");
-        assert_eq!(result, (0, 0));
+    #[rstest]
+    #[case("")]
+    #[case(" \n\t\n")]
+    fn test_empty_input(#[case] input: &str) {
+        let error = puzzle(input).unwrap_err();
+        assert_eq!((error.line(), error.column()), (1, 1));
+        assert_eq!(error.expected(), ["a row of `.` and `@`"]);
     }
 
     #[test]
@@ -83,13 +338,216 @@ mod tests {
 @.@@@.@@@@
 .@@@@@@@@.
 @.@.@@@.@.",
+        )
+        .unwrap();
+        assert_eq!((result.first_round, result.total), (13, 43));
+        assert_eq!(result.rounds, 9);
+        let removed_in = result.removed_in().unwrap();
+        assert_eq!(removed_in[(0, 2)], Some(1));
+        assert_eq!(removed_in[(0, 0)], None);
+        assert_eq!(
+            result.render().unwrap(),
+            "\
+..11.1121.
+134.2.2.32
+24578.1.33
+2.69@@..2.
+13.@@@@.21
+.24@@@@@.2
+.2.@.@.@@3
+1.4@@.@@@4
+.23@@@@@5.
+1.1.@@@.1."
+        );
+    }
+
+    // the original round by round convolution, as the reference for `RemovalRounds`
+    fn convolution_rounds(mut paper: Grid<u8>) -> Vec<usize> {
+        use ndarray::array;
+        use ndarray_conv::{ConvExt, ConvMode, PaddingMode};
+
+        let kernel = array![[1u8, 1, 1], [1, 0, 1], [1, 1, 1]];
+        let mut rounds = Vec::new();
+        while !paper.is_empty() {
+            let sums = paper
+                .as_array()
+                .conv(&kernel, ConvMode::Same, PaddingMode::Zeros)
+                .unwrap();
+            let mut removed = 0;
+            for (cell, sum) in paper.iter_mut().zip(sums.iter()) {
+                if *cell == 1 && *sum < 4 {
+                    *cell = 0;
+                    removed += 1;
+                }
+            }
+            if removed == 0 {
+                break;
+            }
+            rounds.push(removed);
+        }
+        rounds
+    }
+
+    // a deterministic pseudo random grid with about `density` percent paper
+    fn generated_grid(rows: usize, columns: usize, density: u64) -> Grid<u8> {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        Grid::new(ndarray::Array2::from_shape_fn((rows, columns), |_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            u8::from(state % 100 < density)
+        }))
+    }
+
+    #[test]
+    #[rstest]
+    #[case(1, 1, 100)]
+    #[case(1, 50, 90)]
+    #[case(50, 1, 90)]
+    #[case(40, 60, 60)]
+    #[case(100, 100, 75)]
+    #[case(100, 100, 90)]
+    fn test_rounds_match_convolution(
+        #[case] rows: usize,
+        #[case] columns: usize,
+        #[case] density: u64,
+    ) {
+        let paper = generated_grid(rows, columns, density);
+        let rounds: Vec<_> = Paper::Dense(paper.map(|cell| *cell == 1))
+            .removal_rounds()
+            .map(|removed| removed.len())
+            .collect();
+
+        assert_eq!(rounds, convolution_rounds(paper));
+    }
+
+    #[test]
+    #[rstest]
+    #[case("@@.\n.@", "@@.\n.@.")]
+    #[case("@\r\n\r\n.@@\n  \n", "@..\n...\n.@@")]
+    #[case("\n@", ".\n@")]
+    #[case("..@\n\t\n", "..@")]
+    fn test_padded_rows(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(Day4::parse(input).unwrap().to_string(), expected);
+    }
+
+    #[test]
+    #[rstest]
+    #[case("@@ \n", (1, 3))]
+    #[case("@@\r", (1, 3))]
+    #[case("@\n \n@", (2, 1))]
+    #[case("@x", (1, 2))]
+    fn test_invalid_rows(#[case] input: &str, #[case] position: (usize, usize)) {
+        let error = Day4::parse(input).unwrap_err();
+        assert_eq!((error.line(), error.column()), position);
+    }
+
+    #[test]
+    #[rstest]
+    #[case(1, 1, 100)]
+    #[case(40, 60, 60)]
+    #[case(100, 100, 75)]
+    #[case(200, 300, 2)]
+    fn test_sparse_matches_dense(
+        #[case] rows: usize,
+        #[case] columns: usize,
+        #[case] density: u64,
+    ) {
+        let dense = Paper::Dense(generated_grid(rows, columns, density).map(|cell| *cell == 1));
+        let sparse = Paper::Sparse {
+            rows,
+            columns,
+            rolls: (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (row, column)))
+                .filter(|position| dense.contains(*position))
+                .collect(),
+        };
+
+        let sorted = |paper: &Paper| -> Vec<_> {
+            paper
+                .removal_rounds()
+                .map(|mut removed| {
+                    removed.sort_unstable();
+                    removed
+                })
+                .collect()
+        };
+        assert_eq!(sorted(&sparse), sorted(&dense));
+        assert_eq!(sparse.to_grid(), dense.to_grid());
+        assert_eq!(
+            Removal::new(sparse).removed_in(),
+            Removal::new(dense).removed_in()
         );
-        assert_eq!(result, (13, 43));
     }
 
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day4_input.txt"));
-        assert_eq!(result, (1435, 8623));
+    fn test_backend_by_density() {
+        assert!(matches!(Day4::parse("@.\n.@").unwrap(), Paper::Dense(_)));
+
+        let input = format!("{}@\n", ".".repeat(999)).repeat(100);
+        let result = puzzle(&input).unwrap();
+        assert!(matches!(result.paper, Paper::Sparse { .. }));
+        assert_eq!((result.first_round, result.total), (100, 100));
+    }
+
+    #[test]
+    fn test_huge_sparse_sheet() {
+        // a million by a million cells, nothing can be stored per cell
+        let side = 1_000_000;
+        let input = format!("@{}{}@", "\n".repeat(side - 1), ".".repeat(side - 1));
+
+        let result = puzzle(&input).unwrap();
+        assert!(matches!(result.paper, Paper::Sparse { .. }));
+        assert_eq!((result.first_round, result.total, result.rounds), (2, 2, 1));
+        assert_eq!(result.paper.to_grid(), None);
+        assert_eq!(result.removed_in(), None);
+        assert_eq!(result.render(), None);
+
+        let mut frames = FrameWriter::new(std::env::temp_dir(), Default::default());
+        assert!(matches!(
+            visualise(&input, &mut frames),
+            Err(VisualiseError::TooLarge {
+                rows: 1_000_000,
+                columns: 1_000_000
+            })
+        ));
+        assert_eq!(frames.frames(), 0);
+    }
+
+    #[test]
+    fn test_ragged_rows() {
+        let result = puzzle("@@@\n@\n\n.@@@@\n").unwrap();
+
+        assert_eq!((result.paper.rows(), result.paper.columns()), (4, 5));
+        assert_eq!(result.render().unwrap(), "111..\n1....\n.....\n.1111");
+        assert_eq!(result, puzzle("@@@..\n@....\n.....\n.@@@@").unwrap());
+    }
+
+    #[test]
+    fn test_visualise() {
+        let dir = std::env::temp_dir().join(format!("y2025-day4-{}", std::process::id()));
+        let mut frames = FrameWriter::new(&dir, Default::default()).with_scale(1);
+
+        visualise(
+            "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n@.@@@@..@.\n@@.@@@@.@@\n.@@@@@@@.@\n.@.@.@.@@@\n@.@@@.@@@@\n.@@@@@@@@.\n@.@.@@@.@.",
+            &mut frames,
+        )
+        .unwrap();
+
+        // the initial grid and one frame for each of the 9 rounds that removed paper
+        assert_eq!(frames.frames(), 10);
+        assert!(dir.join("frame-0010.ppm").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = puzzle("..@@\n@#@.\n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 2));
+        assert_eq!(error.expected(), ["`.`", "`@`"]);
+
+        let error = puzzle("..@@\n@.@. \n").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 5));
+        assert_eq!(error.expected(), ["`.`", "`@`"]);
     }
 }
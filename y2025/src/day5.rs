@@ -1,85 +1,318 @@
-use std::ops::RangeInclusive;
+use std::{collections::BTreeMap, ops::RangeInclusive, str::FromStr};
 
-use itertools::Itertools;
+use aoc_core::{Answer, ParseError, Solution};
 use rangemap::RangeInclusiveSet;
 use rayon::prelude::*;
 use winnow::Parser;
 
-pub fn puzzle(input: &str) -> (u64, u64) {
-    let input = Ingredients::from_str(input);
+pub struct Day5;
 
-    (input.count_fresh(), input.total_fresh())
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Parsed<'a> = Ingredients;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        input.parse()
+    }
+
+    fn part1(ingredients: &Self::Parsed<'_>) -> Answer {
+        ingredients.count_fresh().into()
+    }
+
+    fn part2(ingredients: &Self::Parsed<'_>) -> Answer {
+        ingredients.total_fresh().into()
+    }
+}
+
+pub fn puzzle(input: &str) -> Result<(u64, u128), ParseError> {
+    let input: Ingredients = input.parse()?;
+
+    Ok((input.count_fresh(), input.total_fresh()))
+}
+
+/// Inventories of at least this many items are counted by merge-join rather than by lookup.
+///
+/// The crossover measured with the `day5_counting` benchmark on a single core, sort included:
+/// from 2^10 items on the merge-join takes about half the time (44 µs against 93 µs, up to
+/// 1.0 s against 1.4 s at 2^24), up to 2^8 items both take 30 to 50 µs building the ranges and
+/// differ by less than the noise. Not measured on more cores, where both run in parallel.
+pub const MERGE_JOIN_MIN: usize = 1 << 10;
+
+/// How [`Ingredients::count_fresh`] finds the fresh items of the inventory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Counting {
+    /// Every item is looked up in the fresh ranges
+    #[default]
+    Lookup,
+    /// The inventory is sorted once and walked alongside the ranges, skipping to every range by
+    /// binary search
+    MergeJoin,
+}
+
+impl Counting {
+    /// Merge-join from [`MERGE_JOIN_MIN`] items on, lookup below.
+    pub fn for_inventory(items: usize) -> Self {
+        if items >= MERGE_JOIN_MIN {
+            Counting::MergeJoin
+        } else {
+            Counting::Lookup
+        }
+    }
 }
+
 // use RangeInclusive instead of FreshRange
 // use rangemap::RangeInclusiveSet for part 2
 #[derive(Debug, PartialEq, Default)]
 pub struct Ingredients {
     fresh: RangeInclusiveSet<u64>,
+    // the number of fresh IDs before every merged range, keyed by its start, for counting
+    fresh_before: BTreeMap<u64, u128>,
+    // sorted for the merge-join, otherwise as given
     inventory: Vec<u64>,
+    counting: Counting,
 }
 
 impl Ingredients {
-    fn from_str(input: &str) -> Self {
-        RangeInclusiveSet::from_iter(vec![RangeInclusive::new(0, 0)]);
-        parsing::parse_ingredients.parse(input).unwrap_or_default()
+    /// Counts the inventory as [`Counting::for_inventory`] picks for its size.
+    pub fn new(fresh: impl IntoIterator<Item = RangeInclusive<u64>>, inventory: Vec<u64>) -> Self {
+        let counting = Counting::for_inventory(inventory.len());
+        Self::with_counting(fresh, inventory, counting)
+    }
+
+    /// Only sorts the inventory for [`Counting::MergeJoin`], so counting never needs another
+    /// copy of it.
+    pub fn with_counting(
+        fresh: impl IntoIterator<Item = RangeInclusive<u64>>,
+        mut inventory: Vec<u64>,
+        counting: Counting,
+    ) -> Self {
+        let fresh = RangeInclusiveSet::from_iter(fresh);
+        let fresh_before = fresh
+            .iter()
+            .scan(0, |before, range| {
+                let start = (*range.start(), *before);
+                *before += range_len(range);
+                Some(start)
+            })
+            .collect();
+        if counting == Counting::MergeJoin {
+            inventory.par_sort_unstable();
+        }
+
+        Self {
+            fresh,
+            fresh_before,
+            inventory,
+            counting,
+        }
+    }
+
+    pub fn fresh(&self) -> &RangeInclusiveSet<u64> {
+        &self.fresh
+    }
+
+    /// The inventory, in ascending order when it is counted by [`Counting::MergeJoin`].
+    pub fn inventory(&self) -> &[u64] {
+        &self.inventory
     }
-    fn count_fresh(&self) -> u64 {
+
+    pub fn is_fresh(&self, id: u64) -> bool {
+        self.fresh.contains(&id)
+    }
+
+    /// The merged fresh range `id` is in, if it is fresh.
+    pub fn fresh_range(&self, id: u64) -> Option<&RangeInclusive<u64>> {
+        self.fresh.get(&id)
+    }
+
+    /// The ranges of IDs in `span` that are not fresh, in order.
+    pub fn gaps<'a>(
+        &'a self,
+        span: &'a RangeInclusive<u64>,
+    ) -> impl Iterator<Item = RangeInclusive<u64>> + 'a {
+        self.fresh.gaps(span)
+    }
+
+    /// The fresh ID closest to `id`, the lower one of two as close.
+    pub fn nearest_fresh(&self, id: u64) -> Option<u64> {
+        if self.is_fresh(id) {
+            return Some(id);
+        }
+
+        let below = self
+            .fresh
+            .overlapping(0..=id)
+            .next_back()
+            .map(|range| *range.end());
+        let above = self
+            .fresh
+            .overlapping(id..=u64::MAX)
+            .next()
+            .map(|range| *range.start());
+        match (below, above) {
+            (Some(below), Some(above)) if above - id < id - below => Some(above),
+            (Some(below), _) => Some(below),
+            (None, above) => above,
+        }
+    }
+
+    /// How many of the IDs in `span` are fresh.
+    pub fn count_fresh_in(&self, span: RangeInclusive<u64>) -> u128 {
+        if span.is_empty() {
+            return 0;
+        }
+
+        let before = span
+            .start()
+            .checked_sub(1)
+            .map_or(0, |id| self.fresh_up_to(id));
+        self.fresh_up_to(*span.end()) - before
+    }
+
+    /// How many fresh IDs there are up to and including `id`.
+    fn fresh_up_to(&self, id: u64) -> u128 {
+        let Some(range) = self.fresh.overlapping(0..=id).next_back() else {
+            return 0;
+        };
+        self.fresh_before[range.start()] + range_len(&(*range.start()..=id.min(*range.end())))
+    }
+
+    /// How many items of the inventory are fresh, see [`Counting`] for how they are found.
+    pub fn count_fresh(&self) -> u64 {
+        match self.counting {
+            Counting::Lookup => self.count_fresh_by_lookup(),
+            Counting::MergeJoin => self.count_fresh_by_merge_join(),
+        }
+    }
+
+    fn count_fresh_by_lookup(&self) -> u64 {
         self.inventory
             .par_iter()
             .filter(|item| self.fresh.contains(item))
             .count() as u64
     }
 
-    fn total_fresh(&self) -> u64 {
-        self.fresh
-            .iter()
-            .map(|f| f.try_len().unwrap())
-            .sum::<usize>() as u64
+    fn count_fresh_by_merge_join(&self) -> u64 {
+        let mut inventory = self.inventory.as_slice();
+        let mut count = 0;
+        for range in self.fresh.iter() {
+            inventory = &inventory[inventory.partition_point(|item| item < range.start())..];
+            let fresh = inventory.partition_point(|item| item <= range.end());
+            count += fresh;
+            inventory = &inventory[fresh..];
+        }
+        count as u64
+    }
+
+    /// How many IDs are fresh, up to 2^64 when every ID is.
+    pub fn total_fresh(&self) -> u128 {
+        self.fresh.iter().map(range_len).sum()
+    }
+}
+
+impl FromStr for Ingredients {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parsing::parse_ingredients
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Day5::DAY, e))
     }
 }
 
+fn range_len(range: &RangeInclusive<u64>) -> u128 {
+    u128::from(range.end() - range.start()) + 1
+}
+
 mod parsing {
     use std::ops::RangeInclusive;
 
-    use rangemap::RangeInclusiveSet;
     use winnow::{
-        ascii::{digit1, line_ending},
-        combinator::{separated, seq},
+        ascii::{digit1, line_ending, multispace0},
+        combinator::{alt, cut_err, eof, fail, opt, peek, preceded, separated, seq, terminated},
+        error::{StrContext, StrContextValue},
         prelude::*,
     };
 
     use crate::day5::Ingredients;
 
-    fn parse_fresh_range(input: &mut &str) -> winnow::Result<RangeInclusive<u64>> {
-        seq!(
-            digit1.try_map(str::parse),
-                _: '-',
-                digit1.try_map(str::parse)
+    fn parse_id(input: &mut &str) -> ModalResult<u64> {
+        digit1
+            .try_map(str::parse)
+            .context(StrContext::Label("ingredient ID"))
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
+    }
+
+    // anything after the last number on a line is an error on that line
+    fn line_end(input: &mut &str) -> ModalResult<()> {
+        cut_err(peek(alt((line_ending, eof))))
+            .void()
+            .context(StrContext::Expected(StrContextValue::Description(
+                "end of line",
+            )))
+            .parse_next(input)
+    }
+
+    fn parse_fresh_range(input: &mut &str) -> ModalResult<RangeInclusive<u64>> {
+        let start = input.checkpoint();
+        let (first, last) = seq!(
+            parse_id,
+            _: cut_err('-'.context(StrContext::Expected(StrContextValue::CharLiteral('-')))),
+            cut_err(parse_id),
+            _: line_end,
         )
-        .map(|(start, end)| RangeInclusive::new(start, end))
-        .parse_next(input)
+        .context(StrContext::Label("fresh range"))
+        .parse_next(input)?;
+
+        if last < first {
+            input.reset(&start);
+            return cut_err(fail)
+                .context(StrContext::Label("fresh range"))
+                .context(StrContext::Expected(StrContextValue::Description(
+                    "end not before start",
+                )))
+                .parse_next(input);
+        }
+
+        Ok(first..=last)
     }
 
-    fn parse_fresh_range_list(input: &mut &str) -> winnow::Result<Vec<RangeInclusive<u64>>> {
+    fn parse_fresh_range_list(input: &mut &str) -> ModalResult<Vec<RangeInclusive<u64>>> {
         separated(0.., parse_fresh_range, line_ending).parse_next(input)
     }
 
-    fn parse_inventory_list(input: &mut &str) -> winnow::Result<Vec<u64>> {
-        separated(0.., digit1.try_map(str::parse::<u64>), line_ending)
-            // .map(|(a, _)| a)
-            .parse_next(input)
+    fn parse_inventory_list(input: &mut &str) -> ModalResult<Vec<u64>> {
+        separated(0.., terminated(parse_id, line_end), line_ending).parse_next(input)
     }
 
-    pub fn parse_ingredients(input: &mut &str) -> winnow::Result<Ingredients> {
-        seq!(
-            Ingredients {
-                fresh: parse_fresh_range_list.map(RangeInclusiveSet::from_iter),
-                _: line_ending,
-                _: line_ending,
-                inventory: parse_inventory_list,
-            }
+    /// The fresh ranges, then optionally a blank line and the inventory.
+    pub fn parse_ingredients(input: &mut &str) -> ModalResult<Ingredients> {
+        let (fresh, inventory) = (
+            parse_fresh_range_list,
+            opt(preceded((line_ending, line_ending), parse_inventory_list)),
         )
-        .parse_next(input)
+            .parse_next(input)?;
+        let skipped = multispace0.parse_next(input)?;
+
+        // another range or item can only start a line right after one, or the inventory right
+        // after the blank line. Past a further blank line nothing but whitespace may follow
+        let next_line = matches!(skipped, "\n" | "\r\n")
+            && inventory
+                .as_ref()
+                .is_none_or(|inventory| !inventory.is_empty());
+        let expected = if skipped.is_empty() || next_line {
+            "digit"
+        } else {
+            "end of input"
+        };
+        cut_err(eof)
+            .context(StrContext::Label("ingredients"))
+            .context(StrContext::Expected(StrContextValue::Description(expected)))
+            .parse_next(input)?;
+
+        Ok(Ingredients::new(fresh, inventory.unwrap_or_default()))
     }
 
     #[cfg(test)]
@@ -138,7 +371,7 @@ mod parsing {
 2
 3
 4
-5", Ingredients { fresh: RangeInclusiveSet::from_iter(vec![RangeInclusive::new(123, 123)]), inventory: vec![1u64, 2, 3, 4, 5]})]
+5", Ingredients::new(vec![RangeInclusive::new(123, 123)], vec![1u64, 2, 3, 4, 5]))]
         fn test_parse_ingredients(#[case] input: &str, #[case] expected: Ingredients) {
             let (left_over, result) = parse_ingredients.parse_peek(input).unwrap();
 
@@ -150,13 +383,14 @@ mod parsing {
 
 #[cfg(test)]
 mod tests {
+    use rstest::rstest;
     use test_log::test;
 
     use super::*;
 
     #[test]
     fn test_empty_input() {
-        let result = puzzle("");
+        let result = puzzle("").unwrap();
         assert_eq!(result, (0, 0));
     }
 
@@ -175,13 +409,158 @@ mod tests {
 11
 17
 32",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (3, 14));
     }
 
+    fn example() -> Ingredients {
+        Ingredients::new(
+            [3..=5, 10..=14, 16..=20, 12..=18],
+            vec![1, 5, 8, 11, 17, 32],
+        )
+    }
+
+    #[test]
+    #[rstest]
+    #[case(2, false, None)]
+    #[case(3, true, Some(3..=5))]
+    #[case(15, true, Some(10..=20))]
+    #[case(21, false, None)]
+    fn test_fresh_range(
+        #[case] id: u64,
+        #[case] fresh: bool,
+        #[case] expected: Option<RangeInclusive<u64>>,
+    ) {
+        let ingredients = example();
+
+        assert_eq!(ingredients.is_fresh(id), fresh);
+        assert_eq!(ingredients.fresh_range(id), expected.as_ref());
+    }
+
+    #[test]
+    fn test_gaps() {
+        let ingredients = example();
+
+        let gaps: Vec<_> = ingredients.gaps(&(0..=25)).collect();
+        assert_eq!(gaps, [0..=2, 6..=9, 21..=25]);
+        assert_eq!(ingredients.gaps(&(11..=19)).count(), 0);
+    }
+
+    #[test]
+    #[rstest]
+    #[case(0, Some(3))]
+    #[case(4, Some(4))]
+    #[case(7, Some(5))]
+    #[case(8, Some(10))]
+    #[case(1000, Some(20))]
+    fn test_nearest_fresh(#[case] id: u64, #[case] expected: Option<u64>) {
+        assert_eq!(example().nearest_fresh(id), expected);
+        assert_eq!(Ingredients::default().nearest_fresh(id), None);
+    }
+
+    #[test]
+    #[rstest]
+    #[case(0..=u64::MAX, 14)]
+    #[case(0..=2, 0)]
+    #[case(4..=12, 5)]
+    #[case(5..=5, 1)]
+    #[case(RangeInclusive::new(12, 11), 0)]
+    #[case(19..=100, 2)]
+    fn test_count_fresh_in(#[case] span: RangeInclusive<u64>, #[case] expected: u128) {
+        let ingredients = example();
+
+        assert_eq!(ingredients.count_fresh_in(span.clone()), expected);
+        let counted = ingredients.fresh().iter().map(|range| {
+            let (start, end) = (
+                *range.start().max(span.start()),
+                *range.end().min(span.end()),
+            );
+            if start <= end {
+                u128::from(end - start) + 1
+            } else {
+                0
+            }
+        });
+        assert_eq!(counted.sum::<u128>(), expected);
+    }
+
+    #[test]
+    fn test_count_every_id() {
+        let ingredients = Ingredients::new([0..=u64::MAX], Vec::new());
+        assert_eq!(ingredients.total_fresh(), 1 << 64);
+        assert_eq!(ingredients.count_fresh_in(0..=u64::MAX), 1 << 64);
+        assert_eq!(
+            ingredients.count_fresh_in(1..=u64::MAX),
+            u128::from(u64::MAX)
+        );
+    }
+
+    #[test]
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![1, 5, 8, 11, 17, 32])]
+    #[case(vec![32, 17, 11, 8, 5, 1, 5, 17, 3])]
+    #[case((0..30).rev().collect())]
+    fn test_merge_join_matches_lookup(#[case] inventory: Vec<u64>) {
+        let fresh = [3..=5, 10..=14, 16..=20, 12..=18];
+        let lookup = Ingredients::with_counting(fresh.clone(), inventory.clone(), Counting::Lookup);
+        let merge_join = Ingredients::with_counting(fresh, inventory.clone(), Counting::MergeJoin);
+
+        // only the merge-join needs the inventory sorted
+        assert_eq!(lookup.inventory(), inventory);
+        assert!(merge_join.inventory().is_sorted());
+        assert_eq!(merge_join.count_fresh(), lookup.count_fresh());
+    }
+
+    #[test]
+    fn test_large_inventory() {
+        let inventory: Vec<_> = (0..MERGE_JOIN_MIN as u64)
+            .map(|id| id * 7919 % 100_000)
+            .collect();
+        let fresh = [100..=999, 5000..=5000, 20_000..=99_999];
+        let ingredients = Ingredients::new(fresh.clone(), inventory.clone());
+
+        assert_eq!(ingredients.counting, Counting::MergeJoin);
+        assert_eq!(
+            ingredients.count_fresh(),
+            Ingredients::with_counting(fresh, inventory, Counting::Lookup).count_fresh()
+        );
+        assert_eq!(
+            Counting::for_inventory(MERGE_JOIN_MIN - 1),
+            Counting::Lookup
+        );
+    }
+
+    #[test]
+    #[rstest]
+    #[case("3-5\n10-14")]
+    #[case("3-5\n10-14\n")]
+    #[case("3-5\n10-14\n\n")]
+    fn test_ranges_only(#[case] input: &str) {
+        assert_eq!(puzzle(input).unwrap(), (0, 8));
+    }
+
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day5_input.txt"));
-        assert_eq!(result, (720, 357608232770687));
+    #[rstest]
+    #[case("3-5\n10-14\n\n1\n5x\n8", (5, 2), "end of line")]
+    #[case("3-5\n10x14\n\n1", (2, 3), "`-`")]
+    #[case("3-5\n10-\n\n1", (2, 4), "digit")]
+    #[case("3-5\n14-10\n\n1", (2, 1), "end not before start")]
+    #[case("3-5\nabc\n\n1", (2, 1), "digit")]
+    #[case("3-5\n\n1\nabc", (4, 1), "digit")]
+    #[case("3-5\n\nabc", (3, 1), "digit")]
+    #[case("3-5\n\n1\n\n2", (5, 1), "end of input")]
+    #[case("3-5\n\n1\n\n4-6\n", (5, 1), "end of input")]
+    #[case("3-5\n\n1\n5\n\n\n  abc", (7, 3), "end of input")]
+    #[case("3-5\n\n\n1", (4, 1), "end of input")]
+    fn test_invalid_input(
+        #[case] input: &str,
+        #[case] position: (usize, usize),
+        #[case] expected: &str,
+    ) {
+        let error = puzzle(input).unwrap_err();
+        assert_eq!((error.line(), error.column()), position);
+        assert_eq!(error.expected(), [expected]);
     }
 }
@@ -1,11 +1,50 @@
+use aoc_core::{Answer, ParseError, Solution};
 use tracing::debug;
+use winnow::{Parser, ascii::multispace0, combinator::terminated};
 
-pub fn puzzle(input: &str) -> (u64, u64) {
+use crate::day6::parsing::parse_worksheet;
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    // the two parts read the worksheet differently so there is nothing to share up front
+    type Parsed<'a> = &'a str;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        if input.is_empty() {
+            return Ok(input);
+        }
+
+        // only validates the layout, any trailing whitespace is trimmed
+        terminated(parse_worksheet, multispace0)
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(input: &Self::Parsed<'_>) -> Answer {
+        if input.is_empty() {
+            return Answer::from(0u64);
+        }
+        solve_part1(input).into()
+    }
+
+    fn part2(input: &Self::Parsed<'_>) -> Answer {
+        if input.is_empty() {
+            return Answer::from(0u64);
+        }
+        solve_part2(input).into()
+    }
+}
+
+pub fn puzzle(input: &str) -> Result<(u64, u64), ParseError> {
+    let input = Day6::parse(input)?;
     if input.is_empty() {
-        return (0, 0);
+        return Ok((0, 0));
     }
 
-    (solve_part1(input), solve_part2(input))
+    Ok((solve_part1(input), solve_part2(input)))
 }
 
 // these problems are different enough at least when looking at my part 1 solution that I am creating two distinct functions
@@ -72,6 +111,78 @@ fn solve_part2(input: &str) -> u64 {
         .sum()
 }
 
+mod parsing {
+    use winnow::{
+        ascii::line_ending,
+        combinator::{alt, cut_err, eof, not, peek, preceded, repeat, terminated},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+        token::one_of,
+    };
+
+    fn parse_number_cell(input: &mut &str) -> winnow::ModalResult<char> {
+        one_of((' ', '0'..='9'))
+            .context(StrContext::Label("number row"))
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .context(StrContext::Expected(StrContextValue::CharLiteral(' ')))
+            .parse_next(input)
+    }
+
+    fn parse_operator_cell(input: &mut &str) -> winnow::ModalResult<char> {
+        one_of([' ', '*', '+'])
+            .context(StrContext::Label("operator row"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('*')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('+')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral(' ')))
+            .parse_next(input)
+    }
+
+    fn parse_row_end(input: &mut &str) -> winnow::ModalResult<()> {
+        peek(alt((line_ending, eof)))
+            .void()
+            .context(StrContext::Label("row"))
+            .context(StrContext::Expected(StrContextValue::Description(
+                "end of row",
+            )))
+            .parse_next(input)
+    }
+
+    fn parse_rows(input: &mut &str) -> winnow::ModalResult<()> {
+        // every row must be as wide as the first so the columns line up for part 2
+        let width = terminated(
+            repeat::<_, _, (), _, _>(1.., parse_number_cell).take(),
+            cut_err(parse_row_end),
+        )
+        .parse_next(input)?
+        .len();
+
+        repeat::<_, _, (), _, _>(
+            0..,
+            preceded(
+                (line_ending, not(one_of(['*', '+']))),
+                cut_err(terminated(
+                    repeat::<_, _, (), _, _>(width, parse_number_cell),
+                    parse_row_end,
+                )),
+            ),
+        )
+        .parse_next(input)?;
+
+        cut_err(preceded(
+            line_ending,
+            terminated(
+                repeat::<_, _, (), _, _>(width, parse_operator_cell),
+                parse_row_end,
+            ),
+        ))
+        .parse_next(input)
+    }
+
+    pub fn parse_worksheet<'a>(input: &mut &'a str) -> winnow::ModalResult<&'a str> {
+        parse_rows.take().parse_next(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_log::test;
@@ -80,7 +191,7 @@ mod tests {
 
     #[test]
     fn test_empty_input() {
-        let result = puzzle("");
+        let result = puzzle("").unwrap();
         assert_eq!(result, (0, 0));
     }
 
@@ -92,7 +203,8 @@ mod tests {
  45 64  387 23 
   6 98  215 314
 *   +   *   +  ",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (4277556, 3263827));
     }
 
@@ -104,7 +216,8 @@ mod tests {
  45 64  387 23   3
   6 98  215 314 23
 *   +   *   +   + ",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (4277605, 3264182));
     }
 
@@ -116,7 +229,8 @@ mod tests {
  45 64  387 23   3
   6 98  215 314 23
 *   +   *   +   * ",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (4279143, 3271153));
     }
 
@@ -128,13 +242,22 @@ mod tests {
 45 64  387 23 
  6 98  215 314
 *  +   *   +  ",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (4250556, 3263827));
     }
 
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day6_input.txt"));
-        assert_eq!(result, (4719804927602, 9608327000261));
+    fn test_invalid_input() {
+        let error = puzzle("123 328\n 45 6x4\n*   +  ").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 6));
+        assert_eq!(error.expected(), ["digit", "` `"]);
+
+        let error = puzzle("123 328\n 45 64  \n*   +  ").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 8));
+        assert_eq!(error.expected(), ["end of row"]);
+
+        let error = puzzle("123 328\n 45 64").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 7));
     }
 }
@@ -1,23 +1,89 @@
+use aoc_core::{
+    Answer, Grid, ParseError, Solution,
+    visualise::{BLACK, FrameWriter, Rgb, VisualiseError, WHITE},
+};
 use bitvec::prelude::*;
+use ndarray::{Array2, ArrayView1};
 use tracing::{debug, info};
+use winnow::Parser;
 
-pub fn puzzle(input: &str) -> (usize, u64) {
-    let (_, splits, paths) = input
-        .lines()
-        .map(|l| (parse_line(l), 0, vec![]))
-        .filter(|l| l.0.any())
+use crate::day7::parsing::parse_manifold;
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+
+    type Parsed<'a> = Vec<BitVec>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        let manifold = parse_manifold
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))?;
+
+        Ok(manifold
+            .row_iter()
+            .map(row_to_bits)
+            .filter(|l| l.any())
+            .collect())
+    }
+
+    fn part1(manifold: &Self::Parsed<'_>) -> Answer {
+        simulate(manifold).0.into()
+    }
+
+    fn part2(manifold: &Self::Parsed<'_>) -> Answer {
+        simulate(manifold).1.into()
+    }
+}
+
+pub fn puzzle(input: &str) -> Result<(usize, u64), ParseError> {
+    Ok(simulate(&Day7::parse(input)?))
+}
+
+/// Draws the beams moving down the manifold, one frame for every row of splitters they pass.
+pub fn visualise(input: &str, frames: &mut FrameWriter) -> Result<(), VisualiseError> {
+    const BEAM: Rgb = [250, 190, 0];
+
+    let manifold = Day7::parse(input)?;
+    let columns = manifold.first().map_or(0, BitVec::len);
+    let mut cells = Array2::from_elem((manifold.len(), columns), WHITE);
+    for (row, splitters) in manifold.iter().enumerate() {
+        for column in splitters.iter_ones() {
+            cells[(row, column)] = BLACK;
+        }
+    }
+    let mut image = Grid::new(cells);
+
+    let mut beams = None;
+    for (row, splitters) in manifold.iter().enumerate() {
+        let state = match beams {
+            None => (splitters.clone(), 0, vec![]),
+            Some(state) => split_tachyons(state, (splitters.clone(), 0, vec![])),
+        };
+        for column in state.0.iter_ones() {
+            image[(row, column)] = BEAM;
+        }
+        frames.write(&image, |colour| *colour)?;
+        beams = Some(state);
+    }
+
+    Ok(())
+}
+
+fn simulate(manifold: &[BitVec]) -> (usize, u64) {
+    let (_, splits, paths) = manifold
+        .iter()
+        .map(|l| (l.clone(), 0, vec![]))
         .reduce(split_tachyons)
         .unwrap_or_default();
     (splits, paths.iter().sum())
 }
 
-fn parse_line(line: &str) -> BitVec {
-    let mut tachyon_splitters = bitvec![0; line.len()];
-    for (i, c) in line.chars().enumerate() {
-        tachyon_splitters.set(i, c != '.');
-    }
+fn row_to_bits(row: ArrayView1<bool>) -> BitVec {
+    let tachyon_splitters: BitVec = row.iter().copied().collect();
 
-    debug!("{line} => {tachyon_splitters:015b}");
+    debug!("{tachyon_splitters:015b}");
 
     tachyon_splitters
 }
@@ -29,9 +95,9 @@ fn split_tachyons(
     let splitter_hits = current_beams.clone() & &tachyon_splitters;
     let splitter_beams_removed = !splitter_hits.clone() & &current_beams;
     let mut split_left = splitter_hits.clone();
-    split_left.shift_left(1);
+    split_left.shift_start(1);
     let mut split_right = splitter_hits.clone();
-    split_right.shift_right(1);
+    split_right.shift_end(1);
 
     let result = splitter_beams_removed | split_left | split_right;
 
@@ -61,6 +127,29 @@ fn split_tachyons(
     (result, new_count, particles)
 }
 
+mod parsing {
+    use aoc_core::{Grid, grid::char_map_input};
+    use winnow::{
+        combinator::alt,
+        error::{StrContext, StrContextValue},
+        prelude::*,
+    };
+
+    // the start and the splitters are both marked as set
+    fn parse_cell(input: &mut &str) -> winnow::ModalResult<bool> {
+        alt(('.'.value(false), '^'.value(true), 'S'.value(true)))
+            .context(StrContext::Label("manifold row"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('.')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('^')))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('S')))
+            .parse_next(input)
+    }
+
+    pub fn parse_manifold(input: &mut &str) -> winnow::ModalResult<Grid<bool>> {
+        char_map_input(parse_cell).parse_next(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -75,8 +164,9 @@ mod tests {
     #[case(".......^.......", bitvec!(0,0,0,0,0,0,0,1,0,0,0,0,0,0,0))]
     #[case("......^.^......", bitvec!(0,0,0,0,0,0,1,0,1,0,0,0,0,0,0))]
     #[case(".^.^.^.^.^...^.", bitvec!(0,1,0,1,0,1,0,1,0,1,0,0,0,1,0))]
-    fn test_parse_line(#[case] input: &str, #[case] expected: BitVec) {
-        let result = parse_line(input);
+    fn test_row_to_bits(#[case] input: &str, #[case] expected: BitVec) {
+        let manifold = parse_manifold.parse(input).unwrap();
+        let result = row_to_bits(manifold.row_iter().next().unwrap());
         assert_eq!(result, expected);
     }
 
@@ -96,7 +186,7 @@ mod tests {
 
     #[test]
     fn test_empty_input() {
-        let result = puzzle("");
+        let result = puzzle("").unwrap();
         assert_eq!(result, (0, 0));
     }
 
@@ -120,13 +210,32 @@ mod tests {
 ...............
 .^.^.^.^.^...^.
 ...............",
-        );
+        )
+        .unwrap();
         assert_eq!(result, (21, 40));
     }
 
     #[test]
-    fn test_input() {
-        let result = puzzle(include_str!("day7_input.txt"));
-        assert_eq!(result, (1539, 6479180385864));
+    fn test_visualise() {
+        let dir = std::env::temp_dir().join(format!("y2025-day7-{}", std::process::id()));
+        let mut frames = FrameWriter::new(&dir, Default::default()).with_scale(1);
+
+        visualise(
+            ".......S.......\n...............\n.......^.......\n...............\n......^.^......",
+            &mut frames,
+        )
+        .unwrap();
+
+        // rows without a splitter are skipped
+        assert_eq!(frames.frames(), 3);
+        assert!(dir.join("frame-0003.ppm").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let error = puzzle(".......S.......\n...............\n.......v.......").unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 8));
+        assert_eq!(error.expected(), ["`.`", "`^`", "`S`"]);
     }
 }
@@ -1,14 +1,45 @@
 use std::cmp::Ordering;
 
+use aoc_core::{Answer, ParseError, Solution};
 use bitvec::prelude::*;
 use glam::Vec3;
 use itertools::Itertools;
 use tracing::info;
+use winnow::Parser;
 
-pub fn puzzle_1(input: &str, connection_count: usize) -> usize {
-    let input = parse(input);
+use crate::day8::parsing::parse_junctions;
 
-    let combinations = create_combinations(&input);
+pub struct Day8;
+
+// the puzzle connects a fixed number of junctions, the example only uses 10
+const CONNECTION_COUNT: usize = 1_000;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+
+    type Parsed<'a> = Vec<Vec3>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_junctions
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(junctions: &Self::Parsed<'_>) -> Answer {
+        largest_circuits(junctions, CONNECTION_COUNT).into()
+    }
+
+    fn part2(junctions: &Self::Parsed<'_>) -> Answer {
+        last_connection(junctions).into()
+    }
+}
+
+pub fn puzzle_1(input: &str, connection_count: usize) -> Result<usize, ParseError> {
+    Ok(largest_circuits(&Day8::parse(input)?, connection_count))
+}
+
+fn largest_circuits(input: &[Vec3], connection_count: usize) -> usize {
+    let combinations = create_combinations(input);
 
     let connections = create_connections(combinations.take(connection_count), input.len());
 
@@ -70,10 +101,12 @@ fn create_connections(
     connections
 }
 
-pub fn puzzle_2(input: &str) -> f32 {
-    let input = parse(input);
+pub fn puzzle_2(input: &str) -> Result<f32, ParseError> {
+    Ok(last_connection(&Day8::parse(input)?))
+}
 
-    let combinations = create_combinations(&input);
+fn last_connection(input: &[Vec3]) -> f32 {
+    let combinations = create_combinations(input);
 
     let input_size = input.len();
 
@@ -121,25 +154,56 @@ pub fn puzzle_2(input: &str) -> f32 {
     0.
 }
 
-fn parse(input: &str) -> Vec<Vec3> {
-    input
-        .lines()
-        .map(|line| {
-            let numbers: Vec<_> = line
-                .split(',')
-                .map(|digits| digits.parse::<u64>().unwrap() as f32)
-                .collect();
-            Vec3::from_slice(&numbers)
-        })
-        .collect()
-}
-
 fn order_by_distance(a: &(&Vec3, &Vec3), b: &(&Vec3, &Vec3)) -> Ordering {
     a.0.distance_squared(*a.1)
         .partial_cmp(&b.0.distance_squared(*b.1))
         .unwrap_or(std::cmp::Ordering::Less)
 }
 
+mod parsing {
+    use glam::Vec3;
+    use winnow::{
+        ascii::{digit1, multispace0},
+        combinator::{cut_err, eof, preceded, repeat_till, seq, terminated},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+    };
+
+    fn parse_coordinate(input: &mut &str) -> winnow::ModalResult<f32> {
+        digit1
+            .try_map(str::parse::<u64>)
+            .map(|coordinate| coordinate as f32)
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
+    }
+
+    fn parse_separator(input: &mut &str) -> winnow::ModalResult<char> {
+        ','.context(StrContext::Expected(StrContextValue::CharLiteral(',')))
+            .parse_next(input)
+    }
+
+    fn parse_junction(input: &mut &str) -> winnow::ModalResult<Vec3> {
+        seq!(Vec3 {
+            x: parse_coordinate,
+            _: parse_separator,
+            y: parse_coordinate,
+            _: parse_separator,
+            z: parse_coordinate,
+        })
+        .context(StrContext::Label("junction box"))
+        .parse_next(input)
+    }
+
+    pub fn parse_junctions(input: &mut &str) -> winnow::ModalResult<Vec<Vec3>> {
+        preceded(
+            multispace0,
+            repeat_till(0.., terminated(cut_err(parse_junction), multispace0), eof),
+        )
+        .map(|(junctions, _)| junctions)
+        .parse_next(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_log::test;
@@ -168,29 +232,22 @@ mod tests {
 984,92,344
 425,690,689";
 
-    const INPUT: &str = include_str!("day8_input.txt");
-
     #[test]
     fn test_puzzle_1_example_input() {
-        let result = puzzle_1(EXAMPLE, 10);
+        let result = puzzle_1(EXAMPLE, 10).unwrap();
         assert_eq!(result, 40);
     }
 
-    #[test]
-    fn test_puzzle_1_input() {
-        let result = puzzle_1(INPUT, 1_000);
-        assert_eq!(result, 129564);
-    }
-
     #[test]
     fn test_puzzle_2_example_input() {
-        let result = puzzle_2(EXAMPLE);
+        let result = puzzle_2(EXAMPLE).unwrap();
         assert_eq!(result, 25272.);
     }
 
     #[test]
-    fn test_puzzle_2_input() {
-        let result = puzzle_2(INPUT);
-        assert_eq!(result, 42047840.0);
+    fn test_invalid_input() {
+        let error = puzzle_2("162,817,812\n57,618").unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 7));
+        assert_eq!(error.expected(), ["`,`"]);
     }
 }
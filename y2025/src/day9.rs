@@ -1,9 +1,37 @@
+use aoc_core::{Answer, ParseError, Solution};
 use glam::U64Vec2;
 use itertools::Itertools;
+use winnow::Parser;
 
-pub fn part1(input: &str) -> u64 {
-    let red_tiles = parse(input);
+use crate::day9::parsing::parse_red_tiles;
 
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+
+    type Parsed<'a> = Vec<U64Vec2>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_red_tiles
+            .parse(input)
+            .map_err(|e| ParseError::from_winnow(Self::DAY, e))
+    }
+
+    fn part1(red_tiles: &Self::Parsed<'_>) -> Answer {
+        largest_rectangle(red_tiles).into()
+    }
+
+    fn part2(red_tiles: &Self::Parsed<'_>) -> Answer {
+        largest_enclosed_rectangle(red_tiles).into()
+    }
+}
+
+pub fn part1(input: &str) -> Result<u64, ParseError> {
+    Ok(largest_rectangle(&Day9::parse(input)?))
+}
+
+fn largest_rectangle(red_tiles: &[U64Vec2]) -> u64 {
     red_tiles
         .iter()
         .tuple_combinations()
@@ -12,24 +40,16 @@ pub fn part1(input: &str) -> u64 {
         .unwrap_or_default()
 }
 
-fn parse(input: &str) -> Vec<U64Vec2> {
-    input
-        .lines()
-        .map(|l| {
-            let (x, y) = l.split_once(',').unwrap();
-            U64Vec2::new(x.parse().unwrap(), y.parse().unwrap())
-        })
-        .collect()
-}
-
 fn calc_area((corner_a, corner_b): (&U64Vec2, &U64Vec2)) -> u64 {
     let diff = (corner_a.max(*corner_b) - corner_a.min(*corner_b)) + 1;
     diff.x * diff.y
 }
 
-pub fn part2(input: &str) -> u64 {
-    let red_tiles = parse(input);
+pub fn part2(input: &str) -> Result<u64, ParseError> {
+    Ok(largest_enclosed_rectangle(&Day9::parse(input)?))
+}
 
+fn largest_enclosed_rectangle(red_tiles: &[U64Vec2]) -> u64 {
     let lines: Vec<(_, _)> = red_tiles.iter().circular_tuple_windows().collect();
 
     red_tiles
@@ -68,6 +88,42 @@ fn bounding_box_check(
     left || right || top || bottom
 }
 
+mod parsing {
+    use glam::U64Vec2;
+    use winnow::{
+        ascii::{digit1, multispace0},
+        combinator::{cut_err, eof, preceded, repeat_till, seq, terminated},
+        error::{StrContext, StrContextValue},
+        prelude::*,
+    };
+
+    fn parse_coordinate(input: &mut &str) -> winnow::ModalResult<u64> {
+        digit1
+            .try_map(str::parse)
+            .context(StrContext::Expected(StrContextValue::Description("digit")))
+            .parse_next(input)
+    }
+
+    fn parse_red_tile(input: &mut &str) -> winnow::ModalResult<U64Vec2> {
+        seq!(U64Vec2 {
+            x: parse_coordinate,
+            _: ','.context(StrContext::Expected(StrContextValue::CharLiteral(','))),
+            y: parse_coordinate,
+        })
+        .context(StrContext::Label("red tile"))
+        .parse_next(input)
+    }
+
+    pub fn parse_red_tiles(input: &mut &str) -> winnow::ModalResult<Vec<U64Vec2>> {
+        preceded(
+            multispace0,
+            repeat_till(0.., terminated(cut_err(parse_red_tile), multispace0), eof),
+        )
+        .map(|(red_tiles, _)| red_tiles)
+        .parse_next(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use test_log::test;
@@ -84,28 +140,21 @@ mod tests {
 2,3
 7,3";
 
-    const INPUT: &str = include_str!("day9_input.txt");
-
     #[test]
     fn test_part1_example_input() {
-        let result = part1(EXAMPLE);
+        let result = part1(EXAMPLE).unwrap();
         assert_eq!(result, 50);
     }
-
-    #[test]
-    fn test_part1_input() {
-        let result = part1(INPUT);
-        assert_eq!(result, 4758121828);
-    }
     #[test]
     fn test_part2_example_input() {
-        let result = part2(EXAMPLE);
+        let result = part2(EXAMPLE).unwrap();
         assert_eq!(result, 24);
     }
 
     #[test]
-    fn test_part2_input() {
-        let result = part2(INPUT);
-        assert_eq!(result, 1577956170);
+    fn test_invalid_input() {
+        let error = part1("7,1\n11,1\n11;7").unwrap_err();
+        assert_eq!((error.line(), error.column()), (3, 3));
+        assert_eq!(error.expected(), ["`,`"]);
     }
 }
@@ -10,3 +10,108 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+
+pub use aoc_core::ParseError;
+
+use aoc_core::{
+    Answer, Solution,
+    visualise::{FrameWriter, VisualiseError},
+};
+
+pub const YEAR: u16 = 2025;
+
+/// Runs both parts of `day` against `input`.
+///
+/// Returns `None` if there is no solution for `day`.
+pub fn run(day: u8, input: &str) -> Option<Result<(Answer, Answer), ParseError>> {
+    let answers = match day {
+        1 => day1::Day1::solve(input),
+        2 => day2::Day2::solve(input),
+        3 => day3::Day3::solve(input),
+        4 => day4::Day4::solve(input),
+        5 => day5::Day5::solve(input),
+        6 => day6::Day6::solve(input),
+        7 => day7::Day7::solve(input),
+        8 => day8::Day8::solve(input),
+        9 => day9::Day9::solve(input),
+        10 => day10::Day10::solve(input),
+        11 => day11::Day11::solve(input),
+        12 => day12::Day12::solve(input),
+        _ => return None,
+    };
+
+    Some(answers)
+}
+
+/// Writes the frames of a visualisation of `day` running against `input`.
+///
+/// Returns `None` if `day` cannot be visualised.
+pub fn visualise(
+    day: u8,
+    input: &str,
+    frames: &mut FrameWriter,
+) -> Option<Result<(), VisualiseError>> {
+    let result = match day {
+        4 => day4::visualise(input, frames),
+        7 => day7::visualise(input, frames),
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn test_run_unknown_day() {
+        assert!(run(0, "").is_none());
+        assert!(run(13, "").is_none());
+    }
+
+    #[test]
+    fn test_run_example_input() {
+        let result = run(5, "3-5\n10-14\n16-20\n12-18\n\n1\n5\n8\n11\n17\n32");
+        assert_eq!(
+            result,
+            Some(Ok((Answer::Unsigned(3), Answer::Unsigned(14))))
+        );
+    }
+
+    // real inputs are not available everywhere, so registered answers without an input are skipped
+    #[test]
+    fn test_registered_answers() {
+        use aoc_core::{AnswerRegistry, InputProvider};
+
+        let workspace = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+        let registry = AnswerRegistry::load(format!("{workspace}/answers.toml")).unwrap();
+        let inputs = InputProvider::from_env(format!("{workspace}/inputs"));
+
+        let mut mismatches = Vec::new();
+        for expected in registry.year(YEAR) {
+            let input = match inputs.load_cached_named(YEAR, expected.day, &expected.input) {
+                Ok(input) => input,
+                Err(error) => {
+                    eprintln!("skipping: {error}");
+                    continue;
+                }
+            };
+            let answers = run(expected.day, &input)
+                .unwrap_or_else(|| panic!("no solution for registered day {}", expected.day))
+                .unwrap();
+            mismatches.extend(expected.check(&answers));
+        }
+
+        let report: Vec<_> = mismatches.iter().map(ToString::to_string).collect();
+        assert!(mismatches.is_empty(), "{}", report.join("\n"));
+    }
+
+    #[test]
+    fn test_run_invalid_input() {
+        let error = run(5, "3-5\n10-14\n\n1\n5x").unwrap().unwrap_err();
+        assert_eq!(error.day(), 5);
+    }
+}